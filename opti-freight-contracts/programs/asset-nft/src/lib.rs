@@ -1,12 +1,108 @@
+#![allow(clippy::too_many_arguments)]
+
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Mint, Token};
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::{self, Burn, FreezeAccount, Mint, ThawAccount, Token, TokenAccount, Transfer};
 
 declare_id!("2ESjYkkwqZYBkAA6gBprX9xaRhqgPVyMyZLkGVAq7YtU");
 
+const MAX_NAME_LEN: usize = 50;
+const MAX_SYMBOL_LEN: usize = 10;
+const MAX_URI_LEN: usize = 200;
+const MAX_SERIES_LEN: usize = 50;
+const FRACTION_DECIMALS: u8 = 0;
+const MAX_INSPECTOR_LEN: usize = 32;
+const MAX_INSURER_LEN: usize = 32;
+pub const MAINTENANCE_PAGE_SIZE: u32 = 10;
+/// Antigüedad máxima de una lectura telemática al publicarse (1 día)
+const MAX_TELEMATICS_AGE: i64 = 24 * 60 * 60;
+pub const VALUATION_HISTORY_LEN: usize = 12;
+const SECONDS_PER_YEAR: i64 = 365 * 24 * 60 * 60;
+pub const DOCUMENT_PAGE_SIZE: u32 = 10;
+pub const MAX_PAUSES: usize = 16;
+/// Duración máxima de una pausa del guardián antes de vencer (7 días)
+pub const MAX_PAUSE_DURATION: i64 = 7 * 24 * 60 * 60;
+/// Espera tras el fin de una pausa antes de volver a pausar el mismo objetivo (3 días)
+pub const PAUSE_COOLDOWN: i64 = 3 * 24 * 60 * 60;
+/// Período de facturación de la renta de los arrendamientos
+pub const SECONDS_PER_MONTH: i64 = 30 * 24 * 60 * 60;
+/// Programa returns_distribution, dueño del vault del pool que recibe las
+/// indemnizaciones a favor de los tenedores (no puede importarse sin ciclo)
+pub const RETURNS_DISTRIBUTION_ID: Pubkey = pubkey!("DVfDdWLdsin4LGgor4B1nNQTSe4oi5F4cfmRVafpeMog");
+
 #[program]
 pub mod asset_nft {
     use super::*;
 
+    pub fn init_series(
+        ctx: Context<InitSeries>,
+        name: String,
+        uri: String,
+        bounds: SeriesBounds,
+        max_trailers: u32,
+        max_total_value: u64,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::InitSeries::DISCRIMINATOR)?;
+        require!(!name.is_empty() && name.len() <= MAX_SERIES_LEN, ErrorCode::SeriesTooLong);
+        require!(uri.len() <= MAX_URI_LEN, ErrorCode::UriTooLong);
+        bounds.validate()?;
+
+        let series = &mut ctx.accounts.series;
+        series.authority = ctx.accounts.authority.key();
+        series.name = name;
+        series.uri = uri;
+        series.bounds = bounds;
+        series.max_trailers = max_trailers;
+        series.max_total_value = max_total_value;
+        series.trailer_count = 0;
+        series.total_value = 0;
+        series.total_tokens = 0;
+        series.tokens_sold = 0;
+        series.status = SeriesStatus::Open;
+        series.created_at = Clock::get()?.unix_timestamp;
+        series.bump = ctx.bumps.series;
+
+        msg!("Series created: {}", series.name);
+        Ok(())
+    }
+
+    pub fn update_series_bounds(
+        ctx: Context<UpdateSeries>,
+        bounds: SeriesBounds,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::UpdateSeriesBounds::DISCRIMINATOR)?;
+        bounds.validate()?;
+        let series = &mut ctx.accounts.series;
+        series.bounds = bounds;
+        msg!("Bounds updated for series: {}", series.name);
+        Ok(())
+    }
+
+    pub fn set_series_status(
+        ctx: Context<UpdateSeries>,
+        status: SeriesStatus,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::SetSeriesStatus::DISCRIMINATOR)?;
+        let series = &mut ctx.accounts.series;
+        series.status = status;
+        msg!("Series {} status set to {:?}", series.name, status);
+        Ok(())
+    }
+
+    /// Traspasa la serie, normalmente a la tesorería de su gobernanza para que
+    /// las ampliaciones de flota se voten. Los trailers ya creados conservan su autoridad.
+    pub fn set_series_authority(
+        ctx: Context<UpdateSeries>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::SetSeriesAuthority::DISCRIMINATOR)?;
+        require!(new_authority != Pubkey::default(), ErrorCode::InvalidAuthority);
+        let series = &mut ctx.accounts.series;
+        series.authority = new_authority;
+        msg!("Series {} authority set to {}", series.name, new_authority);
+        Ok(())
+    }
+
     pub fn create_trailer_nft(
         ctx: Context<CreateTrailerNFT>,
         name: String,
@@ -19,6 +115,67 @@ pub mod asset_nft {
         apy: u16,
         term_years: u8,
     ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::CreateTrailerNft::DISCRIMINATOR)?;
+        require!(!name.is_empty() && name.len() <= MAX_NAME_LEN, ErrorCode::NameTooLong);
+        require!(symbol.len() <= MAX_SYMBOL_LEN, ErrorCode::SymbolTooLong);
+        require!(uri.len() <= MAX_URI_LEN, ErrorCode::UriTooLong);
+
+        let series_account = &ctx.accounts.series_account;
+        require!(series_account.status == SeriesStatus::Open, ErrorCode::SeriesNotOpen);
+        require!(
+            series_account.max_trailers == 0 || series_account.trailer_count < series_account.max_trailers,
+            ErrorCode::SeriesCapReached
+        );
+
+        let bounds = &series_account.bounds;
+        require!(apy >= bounds.min_apy && apy <= bounds.max_apy, ErrorCode::ApyOutOfBounds);
+        require!(
+            term_years > 0 && term_years >= bounds.min_term_years && term_years <= bounds.max_term_years,
+            ErrorCode::TermOutOfBounds
+        );
+        require!(
+            total_tokens > 0 && total_tokens >= bounds.min_total_tokens && total_tokens <= bounds.max_total_tokens,
+            ErrorCode::TotalTokensOutOfBounds
+        );
+        require!(token_price > 0, ErrorCode::InvalidTokenPrice);
+
+        let expected_value = token_price
+            .checked_mul(total_tokens as u64)
+            .ok_or(ErrorCode::ValueMismatch)?;
+        require!(total_value == expected_value, ErrorCode::ValueMismatch);
+
+        let mint = &ctx.accounts.mint;
+        require!(mint.decimals == FRACTION_DECIMALS, ErrorCode::InvalidMintDecimals);
+        require!(
+            mint.mint_authority == COption::Some(ctx.accounts.authority.key()),
+            ErrorCode::InvalidMintAuthority
+        );
+        let (freeze_authority, _) = freeze_authority_address(&mint.key());
+        require!(
+            mint.freeze_authority == COption::Some(freeze_authority),
+            ErrorCode::InvalidFreezeAuthority
+        );
+
+        // Registrar el trailer en la serie
+        let series_account = &mut ctx.accounts.series_account;
+        let series_value = series_account
+            .total_value
+            .checked_add(total_value)
+            .ok_or(ErrorCode::SeriesCapReached)?;
+        require!(
+            series_account.max_total_value == 0 || series_value <= series_account.max_total_value,
+            ErrorCode::SeriesCapReached
+        );
+        series_account.total_value = series_value;
+        series_account.total_tokens = series_account
+            .total_tokens
+            .checked_add(total_tokens as u64)
+            .ok_or(ErrorCode::ArithmeticError)?;
+        series_account.trailer_count = series_account
+            .trailer_count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticError)?;
+
         let trailer_asset = &mut ctx.accounts.trailer_asset;
         let clock = Clock::get()?;
 
@@ -32,11 +189,33 @@ pub mod asset_nft {
         trailer_asset.token_price = token_price;
         trailer_asset.total_tokens = total_tokens;
         trailer_asset.tokens_sold = 0;
+        trailer_asset.market_authority = Pubkey::default();
+        trailer_asset.fully_funded_at = 0;
+        trailer_asset.status = AssetStatus::Fundraising;
+        trailer_asset.status_entered_at = [0; AssetStatus::COUNT];
+        trailer_asset.status_entered_at[AssetStatus::Fundraising as usize] = clock.unix_timestamp;
+        trailer_asset.pending_authority = Pubkey::default();
+        trailer_asset.roles = AssetRoles::all(ctx.accounts.authority.key());
+        // `liquidate` exige un aprobador distinto de la autoridad, así que el
+        // trailer no puede liquidarse hasta que se designe uno
+        trailer_asset.liquidation_approver = ctx.accounts.authority.key();
+        trailer_asset.lease_count = 0;
+        trailer_asset.policy_count = 0;
+        trailer_asset.maintenance_count = 0;
+        trailer_asset.last_inspection_at = 0;
+        trailer_asset.oracle = Pubkey::default();
+        trailer_asset.current_value = total_value;
+        trailer_asset.last_appraised_at = clock.unix_timestamp;
+        trailer_asset.depreciation = DepreciationConfig {
+            method: DepreciationMethod::StraightLine,
+            residual_value: 0,
+            rate_bps: 0,
+        };
         trailer_asset.apy = apy;
         trailer_asset.term_years = term_years;
         trailer_asset.is_locked = false;
         trailer_asset.created_at = clock.unix_timestamp;
-        trailer_asset.expiry_at = clock.unix_timestamp + (term_years as i64 * 365 * 24 * 60 * 60);
+        trailer_asset.expiry_at = clock.unix_timestamp + (term_years as i64 * SECONDS_PER_YEAR);
         trailer_asset.bump = ctx.bumps.trailer_asset;
 
         msg!("Trailer NFT created: {}", name);
@@ -47,6 +226,8 @@ pub mod asset_nft {
         ctx: Context<UpdateMetadata>,
         new_uri: String,
     ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::UpdateMetadata::DISCRIMINATOR)?;
+        require!(new_uri.len() <= MAX_URI_LEN, ErrorCode::UriTooLong);
         let trailer_asset = &mut ctx.accounts.trailer_asset;
         trailer_asset.uri = new_uri;
         msg!("Metadata updated for trailer: {}", trailer_asset.name);
@@ -57,59 +238,1781 @@ pub mod asset_nft {
         ctx: Context<SetLockStatus>,
         is_locked: bool,
     ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::SetLockStatus::DISCRIMINATOR)?;
         let trailer_asset = &mut ctx.accounts.trailer_asset;
         trailer_asset.is_locked = is_locked;
         msg!("Lock status updated for trailer: {} - Locked: {}", trailer_asset.name, is_locked);
         Ok(())
     }
+
+    /// Congela la cuenta de un tenedor mientras el trailer está bloqueado. Solo
+    /// el gestor de bloqueo puede congelar, así no se bloquean escrows ni vendedores.
+    pub fn freeze_holder(ctx: Context<FreezeHolder>) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::FreezeHolder::DISCRIMINATOR)?;
+        require!(ctx.accounts.trailer_asset.is_locked, ErrorCode::NotLocked);
+
+        let mint_key = ctx.accounts.mint.key();
+        let seeds = &[b"freeze_authority".as_ref(), mint_key.as_ref(), &[ctx.bumps.freeze_authority]];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = FreezeAccount {
+            account: ctx.accounts.holder_token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            authority: ctx.accounts.freeze_authority.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token::freeze_account(cpi_ctx)?;
+
+        msg!("Holder account frozen for trailer: {}", ctx.accounts.trailer_asset.name);
+        Ok(())
+    }
+
+    /// Descongela la cuenta de un tenedor una vez desbloqueado el trailer. Un
+    /// trailer en liquidación sigue bloqueado, así que también se puede
+    /// descongelar para liberar escrows.
+    pub fn thaw_holder(ctx: Context<FreezeHolder>) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::ThawHolder::DISCRIMINATOR)?;
+        let trailer_asset = &ctx.accounts.trailer_asset;
+        require!(
+            !trailer_asset.is_locked || trailer_asset.status.is_winding_down(),
+            ErrorCode::AssetLocked
+        );
+
+        thaw(
+            &ctx.accounts.holder_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.freeze_authority,
+            ctx.bumps.freeze_authority,
+            &ctx.accounts.token_program,
+        )?;
+
+        msg!("Holder account thawed for trailer: {}", ctx.accounts.trailer_asset.name);
+        Ok(())
+    }
+
+    pub fn transition_status(
+        ctx: Context<TransitionStatus>,
+        new_status: AssetStatus,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::TransitionStatus::DISCRIMINATOR)?;
+        let trailer_asset = &mut ctx.accounts.trailer_asset;
+        let current = trailer_asset.status;
+        require!(current.can_transition_to(new_status), ErrorCode::InvalidTransition);
+        // La liquidación requiere la firma del aprobador, ver `liquidate`
+        require!(new_status != AssetStatus::Liquidated, ErrorCode::InvalidTransition);
+
+        let now = Clock::get()?.unix_timestamp;
+        trailer_asset.set_status(new_status, now);
+
+        emit!(StatusChanged {
+            trailer_asset: trailer_asset.key(),
+            from: current,
+            to: new_status,
+            at: now,
+        });
+        msg!("Trailer {} moved from {:?} to {:?}", trailer_asset.name, current, new_status);
+        Ok(())
+    }
+
+    /// Primer paso del traspaso de autoridad. Proponer `Pubkey::default()`
+    /// cancela un traspaso pendiente.
+    pub fn propose_authority(
+        ctx: Context<ProposeAuthority>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::ProposeAuthority::DISCRIMINATOR)?;
+        let trailer_asset = &mut ctx.accounts.trailer_asset;
+        trailer_asset.pending_authority = new_authority;
+        msg!("Authority transfer for trailer {} proposed to {}", trailer_asset.name, new_authority);
+        Ok(())
+    }
+
+    /// Segundo paso del traspaso de autoridad. Todos los roles que aún tenía la
+    /// autoridad saliente, incluido el aprobador de liquidación, pasan a la nueva.
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::AcceptAuthority::DISCRIMINATOR)?;
+        let trailer_asset = &mut ctx.accounts.trailer_asset;
+        let previous = trailer_asset.authority;
+        let new_authority = ctx.accounts.pending_authority.key();
+        trailer_asset.authority = new_authority;
+        trailer_asset.pending_authority = Pubkey::default();
+        trailer_asset.roles.reassign(&previous, &new_authority);
+        if trailer_asset.liquidation_approver == previous {
+            trailer_asset.liquidation_approver = new_authority;
+        }
+        if trailer_asset.oracle == previous {
+            trailer_asset.oracle = new_authority;
+        }
+        msg!("Authority for trailer {} transferred from {} to {}", trailer_asset.name, previous, trailer_asset.authority);
+        Ok(())
+    }
+
+    pub fn set_roles(
+        ctx: Context<SetRoles>,
+        roles: AssetRoles,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::SetRoles::DISCRIMINATOR)?;
+        let trailer_asset = &mut ctx.accounts.trailer_asset;
+        trailer_asset.roles = roles;
+        msg!("Roles updated for trailer: {}", trailer_asset.name);
+        Ok(())
+    }
+
+    /// Añade un registro al historial de mantenimiento del trailer. Los registros
+    /// se guardan en páginas de `MAINTENANCE_PAGE_SIZE`; al llenarse la última se abre otra.
+    pub fn record_maintenance(
+        ctx: Context<RecordMaintenance>,
+        record: MaintenanceRecord,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::RecordMaintenance::DISCRIMINATOR)?;
+        let now = Clock::get()?.unix_timestamp;
+        require!(record.performed_at <= now, ErrorCode::FutureTimestamp);
+        require!(record.inspector.len() <= MAX_INSPECTOR_LEN, ErrorCode::InspectorTooLong);
+
+        let trailer_asset = &mut ctx.accounts.trailer_asset;
+        let page = &mut ctx.accounts.log_page;
+        if page.records.is_empty() {
+            page.trailer_asset = trailer_asset.key();
+            page.page = trailer_asset.maintenance_count / MAINTENANCE_PAGE_SIZE;
+            page.bump = ctx.bumps.log_page;
+        }
+
+        if record.work_type == WorkType::Inspection && record.performed_at > trailer_asset.last_inspection_at {
+            trailer_asset.last_inspection_at = record.performed_at;
+        }
+        trailer_asset.maintenance_count += 1;
+
+        msg!(
+            "Maintenance recorded for trailer {}: {:?} at {} km",
+            trailer_asset.name,
+            record.work_type,
+            record.odometer_km
+        );
+        page.records.push(record);
+        Ok(())
+    }
+
+    pub fn set_oracle(
+        ctx: Context<SetOracle>,
+        oracle: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::SetOracle::DISCRIMINATOR)?;
+        let trailer_asset = &mut ctx.accounts.trailer_asset;
+        trailer_asset.oracle = oracle;
+        msg!("Telematics oracle for trailer {} set to {}", trailer_asset.name, oracle);
+        Ok(())
+    }
+
+    /// Publica una lectura telemática. Los contadores son acumulados, así que cada
+    /// campo debe ser monótono respecto de la lectura anterior, y la lectura debe ser reciente.
+    pub fn post_telematics(
+        ctx: Context<PostTelematics>,
+        snapshot: TelematicsSnapshot,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::PostTelematics::DISCRIMINATOR)?;
+        let now = Clock::get()?.unix_timestamp;
+        require!(snapshot.observed_at <= now, ErrorCode::FutureTimestamp);
+        require!(now - snapshot.observed_at <= MAX_TELEMATICS_AGE, ErrorCode::StaleTelematics);
+
+        let telematics = &mut ctx.accounts.telematics;
+        if telematics.snapshot_count == 0 {
+            telematics.trailer_asset = ctx.accounts.trailer_asset.key();
+            telematics.bump = ctx.bumps.telematics;
+        } else {
+            let latest = &telematics.latest;
+            require!(snapshot.observed_at > latest.observed_at, ErrorCode::StaleTelematics);
+            require!(
+                snapshot.odometer_km >= latest.odometer_km
+                    && snapshot.hours_in_service >= latest.hours_in_service
+                    && snapshot.loaded_km >= latest.loaded_km,
+                ErrorCode::NonMonotonicTelematics
+            );
+        }
+
+        telematics.latest = snapshot;
+        telematics.snapshot_count += 1;
+
+        emit!(TelematicsPosted {
+            trailer_asset: telematics.trailer_asset,
+            snapshot: telematics.latest.clone(),
+            loaded_bps: telematics.loaded_bps(),
+        });
+        Ok(())
+    }
+
+    pub fn create_lease(
+        ctx: Context<CreateLease>,
+        lessee_id: [u8; 32],
+        monthly_rate: u64,
+        start_at: i64,
+        end_at: i64,
+        deposit: u64,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::CreateLease::DISCRIMINATOR)?;
+        require!(monthly_rate > 0 && end_at > start_at, ErrorCode::InvalidLease);
+
+        let trailer_asset = &mut ctx.accounts.trailer_asset;
+        ctx.accounts.lease.set_inner(Lease {
+            trailer_asset: trailer_asset.key(),
+            lease_id: trailer_asset.lease_count,
+            lessee_id,
+            monthly_rate,
+            start_at,
+            end_at,
+            deposit,
+            status: LeaseStatus::Active,
+            bump: ctx.bumps.lease,
+            ended_at: 0,
+        });
+        trailer_asset.lease_count += 1;
+
+        msg!("Lease {} created for trailer {}: {} per month", trailer_asset.lease_count - 1, trailer_asset.name, monthly_rate);
+        Ok(())
+    }
+
+    pub fn set_lease_status(
+        ctx: Context<SetLeaseStatus>,
+        status: LeaseStatus,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::SetLeaseStatus::DISCRIMINATOR)?;
+        let lease = &mut ctx.accounts.lease;
+        require!(lease.status.can_transition_to(status), ErrorCode::InvalidTransition);
+        // La renta deja de devengarse al terminar el contrato o caer en impago;
+        // esos estados son finales, así que `ended_at` nunca queda obsoleto
+        if status.is_ended() {
+            lease.ended_at = Clock::get()?.unix_timestamp;
+        }
+        lease.status = status;
+        msg!("Lease {} status set to {:?}", lease.lease_id, status);
+        Ok(())
+    }
+
+    pub fn add_policy(
+        ctx: Context<AddPolicy>,
+        insurer: String,
+        coverage: u64,
+        premium: u64,
+        starts_at: i64,
+        expires_at: i64,
+        insurer_wallet: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::AddPolicy::DISCRIMINATOR)?;
+        require!(!insurer.is_empty() && insurer.len() <= MAX_INSURER_LEN, ErrorCode::InsurerTooLong);
+        require!(
+            coverage > 0 && expires_at > starts_at && insurer_wallet != Pubkey::default(),
+            ErrorCode::InvalidPolicy
+        );
+
+        let trailer_asset = &mut ctx.accounts.trailer_asset;
+        ctx.accounts.policy.set_inner(InsurancePolicy {
+            trailer_asset: trailer_asset.key(),
+            policy_id: trailer_asset.policy_count,
+            insurer,
+            coverage,
+            premium,
+            starts_at,
+            expires_at,
+            claim_count: 0,
+            bump: ctx.bumps.policy,
+            insurer_wallet,
+        });
+        trailer_asset.policy_count += 1;
+
+        msg!("Policy {} added for trailer {}: coverage {}", trailer_asset.policy_count - 1, trailer_asset.name, coverage);
+        Ok(())
+    }
+
+    /// Abre un siniestro contra una póliza vigente y bloquea el activo hasta que
+    /// el gestor de bloqueo lo libere con `set_lock_status`. Solo el gestor de
+    /// bloqueo puede abrirlo, ya que bloquear es su rol.
+    pub fn open_claim(
+        ctx: Context<OpenClaim>,
+        incident_hash: [u8; 32],
+        incident_at: i64,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::OpenClaim::DISCRIMINATOR)?;
+        let now = Clock::get()?.unix_timestamp;
+        require!(incident_at <= now, ErrorCode::FutureTimestamp);
+
+        let policy = &mut ctx.accounts.policy;
+        require!(policy.covers(incident_at), ErrorCode::PolicyNotInForce);
+
+        ctx.accounts.claim.set_inner(InsuranceClaim {
+            policy: policy.key(),
+            trailer_asset: policy.trailer_asset,
+            claim_id: policy.claim_count,
+            incident_hash,
+            incident_at,
+            opened_at: now,
+            status: ClaimStatus::Open,
+            settlement_amount: 0,
+            route: ProceedsRoute::Repair,
+            settled_at: 0,
+            bump: ctx.bumps.claim,
+            repair_payee: Pubkey::default(),
+        });
+        policy.claim_count += 1;
+
+        let trailer_asset = &mut ctx.accounts.trailer_asset;
+        trailer_asset.is_locked = true;
+
+        emit!(ClaimOpened {
+            trailer_asset: trailer_asset.key(),
+            claim: ctx.accounts.claim.key(),
+            incident_hash,
+        });
+        msg!("Claim opened for trailer {}, asset locked", trailer_asset.name);
+        Ok(())
+    }
+
+    /// Registra la decisión del asegurador. Un monto cero rechaza el siniestro;
+    /// si no, el asegurador lo paga desde su cuenta de tokens: la ruta `Holders`
+    /// al vault del pool, donde `deposit_claim_proceeds` lo acredita, y la ruta
+    /// `Repair` a la cuenta de tokens de `repair_payee`.
+    pub fn settle_claim(
+        ctx: Context<SettleClaim>,
+        amount: u64,
+        route: ProceedsRoute,
+        repair_payee: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::SettleClaim::DISCRIMINATOR)?;
+        require!(ctx.accounts.claim.status == ClaimStatus::Open, ErrorCode::ClaimNotOpen);
+        require!(amount <= ctx.accounts.policy.coverage, ErrorCode::ExceedsCoverage);
+
+        if amount > 0 {
+            let payee_account = &ctx.accounts.payee_account;
+            match route {
+                ProceedsRoute::Holders => require_keys_eq!(
+                    payee_account.key(),
+                    pool_vault_address(&ctx.accounts.trailer_asset.key()),
+                    ErrorCode::InvalidPayee
+                ),
+                ProceedsRoute::Repair => require!(
+                    repair_payee != Pubkey::default() && payee_account.owner == repair_payee,
+                    ErrorCode::InvalidPayee
+                ),
+            }
+
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.insurer_payment_account.to_account_info(),
+                to: payee_account.to_account_info(),
+                authority: ctx.accounts.insurer.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+            token::transfer(cpi_ctx, amount)?;
+        }
+
+        let claim = &mut ctx.accounts.claim;
+        if route == ProceedsRoute::Repair {
+            claim.repair_payee = repair_payee;
+        }
+        claim.status = if amount == 0 { ClaimStatus::Denied } else { ClaimStatus::Settled };
+        claim.settlement_amount = amount;
+        claim.route = route;
+        claim.settled_at = Clock::get()?.unix_timestamp;
+
+        emit!(ClaimSettled {
+            trailer_asset: claim.trailer_asset,
+            claim: claim.key(),
+            amount,
+            route,
+        });
+        msg!("Claim {} settled for {} ({:?})", claim.claim_id, amount, route);
+        Ok(())
+    }
+
+    /// Añade una versión de documento al registro. Las entradas nunca se editan
+    /// ni se eliminan; una versión nueva del mismo tipo reemplaza a la anterior.
+    /// Cada `DOCUMENT_PAGE_SIZE` entradas se abre una página nueva.
+    pub fn add_document(
+        ctx: Context<AddDocument>,
+        doc_type: DocumentType,
+        uri: String,
+        sha256: [u8; 32],
+        effective_at: i64,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::AddDocument::DISCRIMINATOR)?;
+        require!(!uri.is_empty() && uri.len() <= MAX_URI_LEN, ErrorCode::UriTooLong);
+
+        let registry = &mut ctx.accounts.registry;
+        if registry.trailer_asset == Pubkey::default() {
+            registry.trailer_asset = ctx.accounts.trailer_asset.key();
+            registry.bump = ctx.bumps.registry;
+        }
+        let page = &mut ctx.accounts.page;
+        if page.entries.is_empty() {
+            page.trailer_asset = registry.trailer_asset;
+            page.page = registry.document_count / DOCUMENT_PAGE_SIZE;
+            page.bump = ctx.bumps.page;
+        }
+
+        let slot = doc_type as usize;
+        let version = registry.versions[slot].checked_add(1).ok_or(ErrorCode::ArithmeticError)?;
+        registry.versions[slot] = version;
+        registry.latest_index[slot] = registry.document_count;
+        registry.document_count += 1;
+        page.entries.push(DocumentEntry {
+            doc_type,
+            uri,
+            sha256,
+            version,
+            effective_at,
+            recorded_at: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Document {:?} v{} registered for trailer {}", doc_type, version, ctx.accounts.trailer_asset.name);
+        Ok(())
+    }
+
+    /// Registra una nueva valuación, conservando las últimas `VALUATION_HISTORY_LEN`
+    pub fn appraise(
+        ctx: Context<Appraise>,
+        value: u64,
+        source: ValuationSource,
+        document_hash: [u8; 32],
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::Appraise::DISCRIMINATOR)?;
+        require!(value > 0, ErrorCode::InvalidValuation);
+        let now = Clock::get()?.unix_timestamp;
+
+        let trailer_asset = &mut ctx.accounts.trailer_asset;
+        trailer_asset.current_value = value;
+        trailer_asset.last_appraised_at = now;
+
+        let history = &mut ctx.accounts.valuation_history;
+        if history.trailer_asset == Pubkey::default() {
+            history.trailer_asset = trailer_asset.key();
+            history.bump = ctx.bumps.valuation_history;
+        }
+        if history.entries.len() == VALUATION_HISTORY_LEN {
+            history.entries.remove(0);
+        }
+        history.entries.push(Valuation {
+            value,
+            nav_per_token: trailer_asset.nav_per_token(),
+            source,
+            document_hash,
+            recorded_at: now,
+        });
+
+        msg!(
+            "Trailer {} appraised at {} ({} per token)",
+            trailer_asset.name,
+            value,
+            trailer_asset.nav_per_token()
+        );
+        Ok(())
+    }
+
+    pub fn get_nav(ctx: Context<GetNav>) -> Result<NavInfo> {
+        let trailer_asset = &ctx.accounts.trailer_asset;
+        Ok(NavInfo {
+            current_value: trailer_asset.current_value,
+            nav_per_token: trailer_asset.nav_per_token(),
+            last_appraised_at: trailer_asset.last_appraised_at,
+        })
+    }
+
+    pub fn set_depreciation(
+        ctx: Context<SetDepreciation>,
+        config: DepreciationConfig,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::SetDepreciation::DISCRIMINATOR)?;
+        let trailer_asset = &mut ctx.accounts.trailer_asset;
+        require!(config.residual_value <= trailer_asset.total_value, ErrorCode::InvalidDepreciation);
+        if config.method == DepreciationMethod::DecliningBalance {
+            require!(
+                config.rate_bps > 0 && config.rate_bps as u64 <= TrailerAsset::BPS_DENOMINATOR,
+                ErrorCode::InvalidDepreciation
+            );
+        }
+
+        trailer_asset.depreciation = config;
+        msg!("Depreciation schedule updated for trailer: {}", trailer_asset.name);
+        Ok(())
+    }
+
+    pub fn get_book_value(ctx: Context<GetBookValue>) -> Result<BookValue> {
+        let trailer_asset = &ctx.accounts.trailer_asset;
+        let now = Clock::get()?.unix_timestamp;
+        let book_value = trailer_asset.book_value_at(now);
+        let book_value_per_token = if trailer_asset.total_tokens == 0 {
+            0
+        } else {
+            book_value / trailer_asset.total_tokens as u64
+        };
+
+        Ok(BookValue {
+            book_value,
+            book_value_per_token,
+            as_of: now,
+        })
+    }
+
+    pub fn set_market_authority(
+        ctx: Context<SetMarketAuthority>,
+        market_authority: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::SetMarketAuthority::DISCRIMINATOR)?;
+        let trailer_asset = &mut ctx.accounts.trailer_asset;
+        trailer_asset.market_authority = market_authority;
+        msg!("Market authority for trailer {} set to {}", trailer_asset.name, market_authority);
+        Ok(())
+    }
+
+    /// La llama el mercado primario (por CPI, firmada por el PDA de la venta)
+    /// cada vez que cambia la cantidad de tokens vendidos.
+    pub fn update_tokens_sold(
+        ctx: Context<UpdateTokensSold>,
+        tokens_sold: u16,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::UpdateTokensSold::DISCRIMINATOR)?;
+        let trailer_asset = &mut ctx.accounts.trailer_asset;
+        require!(tokens_sold <= trailer_asset.total_tokens, ErrorCode::ExceedsTotalTokens);
+
+        let series = &mut ctx.accounts.series;
+        series.tokens_sold = series
+            .tokens_sold
+            .checked_sub(trailer_asset.tokens_sold as u64)
+            .and_then(|sold| sold.checked_add(tokens_sold as u64))
+            .ok_or(ErrorCode::ArithmeticError)?;
+
+        trailer_asset.tokens_sold = tokens_sold;
+        if tokens_sold == trailer_asset.total_tokens {
+            if trailer_asset.fully_funded_at == 0 {
+                trailer_asset.fully_funded_at = Clock::get()?.unix_timestamp;
+            }
+        } else {
+            trailer_asset.fully_funded_at = 0;
+        }
+
+        msg!("Tokens sold for trailer {}: {}/{}", trailer_asset.name, tokens_sold, trailer_asset.total_tokens);
+        Ok(())
+    }
+
+    pub fn get_funding_status(ctx: Context<GetFundingStatus>) -> Result<FundingStatus> {
+        let trailer_asset = &ctx.accounts.trailer_asset;
+        Ok(FundingStatus {
+            tokens_sold: trailer_asset.tokens_sold,
+            total_tokens: trailer_asset.total_tokens,
+            funded_bps: trailer_asset.funded_bps(),
+            fully_funded_at: trailer_asset.fully_funded_at,
+        })
+    }
+
+    /// Abre el rescate al vencimiento una vez que el trailer llega a `expiry_at`.
+    /// Los fondos se depositan aparte con `deposit_redemption_proceeds`.
+    pub fn open_redemption(
+        ctx: Context<OpenRedemption>,
+        sweep_deadline: i64,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::OpenRedemption::DISCRIMINATOR)?;
+        let now = Clock::get()?.unix_timestamp;
+        let trailer_asset = &mut ctx.accounts.trailer_asset;
+        require!(now >= trailer_asset.expiry_at, ErrorCode::NotMatured);
+        require!(trailer_asset.status.is_operational(), ErrorCode::InvalidTransition);
+        require!(sweep_deadline > now, ErrorCode::InvalidSweepDeadline);
+
+        ctx.accounts.redemption.set_inner(Redemption {
+            trailer_asset: trailer_asset.key(),
+            payment_mint: ctx.accounts.payment_mint.key(),
+            kind: RedemptionKind::Maturity,
+            total_proceeds: 0,
+            units_outstanding: trailer_asset.tokens_sold as u64,
+            units_redeemed: 0,
+            amount_paid: 0,
+            opened_at: now,
+            sweep_deadline,
+            closed_at: 0,
+            bump: ctx.bumps.redemption,
+            finalized_at: 0,
+        });
+
+        let previous = trailer_asset.status;
+        trailer_asset.set_status(AssetStatus::Redeeming, now);
+        emit!(StatusChanged {
+            trailer_asset: trailer_asset.key(),
+            from: previous,
+            to: AssetStatus::Redeeming,
+            at: now,
+        });
+
+        msg!("Redemption opened for trailer: {}", trailer_asset.name);
+        Ok(())
+    }
+
+    /// Solo el aprobador actual (gobernanza o un multisig) puede ceder el rol,
+    /// y nunca a la autoridad del trailer
+    pub fn set_liquidation_approver(
+        ctx: Context<SetLiquidationApprover>,
+        liquidation_approver: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::SetLiquidationApprover::DISCRIMINATOR)?;
+        let trailer_asset = &mut ctx.accounts.trailer_asset;
+        require!(liquidation_approver != trailer_asset.authority, ErrorCode::ApproverIsAuthority);
+        trailer_asset.liquidation_approver = liquidation_approver;
+        msg!("Liquidation approver for trailer {} set to {}", trailer_asset.name, liquidation_approver);
+        Ok(())
+    }
+
+    /// Liquida un trailer antes de plazo: congela la negociación, registra el
+    /// motivo y los fondos iniciales, y abre el mismo rescate a prorrata del vencimiento
+    pub fn liquidate(
+        ctx: Context<Liquidate>,
+        reason: LiquidationReason,
+        reason_hash: [u8; 32],
+        proceeds: u64,
+        sweep_deadline: i64,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::Liquidate::DISCRIMINATOR)?;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            ctx.accounts.trailer_asset.status != AssetStatus::Liquidated
+                && ctx.accounts.trailer_asset.status.can_transition_to(AssetStatus::Liquidated),
+            ErrorCode::InvalidTransition
+        );
+        require!(sweep_deadline > now, ErrorCode::InvalidSweepDeadline);
+
+        if proceeds > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.authority_payment_account.to_account_info(),
+                to: ctx.accounts.redemption_vault.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+            token::transfer(cpi_ctx, proceeds)?;
+        }
+
+        let trailer_asset = &mut ctx.accounts.trailer_asset;
+        let units_outstanding = trailer_asset.tokens_sold as u64;
+        ctx.accounts.redemption.set_inner(Redemption {
+            trailer_asset: trailer_asset.key(),
+            payment_mint: ctx.accounts.payment_mint.key(),
+            kind: RedemptionKind::Liquidation { reason, reason_hash },
+            total_proceeds: proceeds,
+            units_outstanding,
+            units_redeemed: 0,
+            amount_paid: 0,
+            opened_at: now,
+            sweep_deadline,
+            closed_at: 0,
+            bump: ctx.bumps.redemption,
+            finalized_at: 0,
+        });
+
+        let previous = trailer_asset.status;
+        trailer_asset.set_status(AssetStatus::Liquidated, now);
+        trailer_asset.is_locked = true;
+
+        emit!(TrailerLiquidated {
+            trailer_asset: trailer_asset.key(),
+            approver: ctx.accounts.liquidation_approver.key(),
+            reason,
+            reason_hash,
+            proceeds,
+            at: now,
+        });
+        emit!(StatusChanged {
+            trailer_asset: trailer_asset.key(),
+            from: previous,
+            to: AssetStatus::Liquidated,
+            at: now,
+        });
+
+        msg!("Trailer {} liquidated: {:?}", trailer_asset.name, reason);
+        Ok(())
+    }
+
+    pub fn deposit_redemption_proceeds(
+        ctx: Context<DepositRedemptionProceeds>,
+        amount: u64,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::DepositRedemptionProceeds::DISCRIMINATOR)?;
+        require!(ctx.accounts.redemption.closed_at == 0, ErrorCode::RedemptionClosed);
+        require!(ctx.accounts.redemption.finalized_at == 0, ErrorCode::ProceedsFinalized);
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.authority_payment_account.to_account_info(),
+            to: ctx.accounts.redemption_vault.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        token::transfer(cpi_ctx, amount)?;
+
+        let redemption = &mut ctx.accounts.redemption;
+        redemption.total_proceeds = redemption
+            .total_proceeds
+            .checked_add(amount)
+            .ok_or(ErrorCode::ArithmeticError)?;
+
+        msg!("Redemption proceeds deposited: {}", amount);
+        Ok(())
+    }
+
+    /// Declara definitivos los fondos del rescate. Sin esto `redeem` pagaría
+    /// a los primeros en rescatar sobre un vault incompleto y los depósitos
+    /// posteriores irían solo a los últimos
+    pub fn finalize_redemption(ctx: Context<FinalizeRedemption>) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::FinalizeRedemption::DISCRIMINATOR)?;
+        require!(ctx.accounts.redemption.closed_at == 0, ErrorCode::RedemptionClosed);
+        let now = Clock::get()?.unix_timestamp;
+        let vault_amount = ctx.accounts.redemption_vault.amount;
+        let redemption = &mut ctx.accounts.redemption;
+        redemption.finalize(vault_amount, now)?;
+
+        msg!("Redemption proceeds finalized: {}", redemption.total_proceeds);
+        Ok(())
+    }
+
+    /// Quema `units` tokens fraccionados y paga al tenedor su parte a prorrata
+    /// de los fondos que quedan en el vault
+    pub fn redeem(ctx: Context<Redeem>, units: u64) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::Redeem::DISCRIMINATOR)?;
+        require!(units > 0, ErrorCode::ArithmeticError);
+        require!(ctx.accounts.trailer_asset.status.is_winding_down(), ErrorCode::RedemptionClosed);
+        require!(ctx.accounts.redemption.closed_at == 0, ErrorCode::RedemptionClosed);
+        let payout = ctx.accounts.redemption.payout_for(units)?;
+
+        // La liquidación bloquea el activo; el rescate es la única salida permitida
+        if ctx.accounts.holder_token_account.is_frozen() {
+            thaw(
+                &ctx.accounts.holder_token_account,
+                &ctx.accounts.mint,
+                &ctx.accounts.freeze_authority,
+                ctx.bumps.freeze_authority,
+                &ctx.accounts.token_program,
+            )?;
+        }
+
+        let cpi_accounts = Burn {
+            mint: ctx.accounts.mint.to_account_info(),
+            from: ctx.accounts.holder_token_account.to_account_info(),
+            authority: ctx.accounts.holder.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        token::burn(cpi_ctx, units)?;
+
+        let trailer_key = ctx.accounts.trailer_asset.key();
+        let seeds = &[b"redemption".as_ref(), trailer_key.as_ref(), &[ctx.accounts.redemption.bump]];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.redemption_vault.to_account_info(),
+            to: ctx.accounts.holder_payment_account.to_account_info(),
+            authority: ctx.accounts.redemption.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token::transfer(cpi_ctx, payout)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let redemption = &mut ctx.accounts.redemption;
+        redemption.units_redeemed = redemption
+            .units_redeemed
+            .checked_add(units)
+            .ok_or(ErrorCode::ArithmeticError)?;
+        redemption.amount_paid = redemption
+            .amount_paid
+            .checked_add(payout)
+            .ok_or(ErrorCode::ArithmeticError)?;
+
+        msg!("Redeemed {} units for {}", units, payout);
+
+        if redemption.units_redeemed == redemption.units_outstanding {
+            redemption.closed_at = now;
+            let trailer_asset = &mut ctx.accounts.trailer_asset;
+            let previous = trailer_asset.status;
+            trailer_asset.set_status(AssetStatus::Redeemed, now);
+            emit!(StatusChanged {
+                trailer_asset: trailer_asset.key(),
+                from: previous,
+                to: AssetStatus::Redeemed,
+                at: now,
+            });
+        }
+        Ok(())
+    }
+
+    /// Pasado el plazo de barrido la autoridad recupera los fondos no reclamados
+    /// y el activo pasa a su estado final
+    pub fn sweep_redemption(ctx: Context<SweepRedemption>) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::SweepRedemption::DISCRIMINATOR)?;
+        let now = Clock::get()?.unix_timestamp;
+        require!(ctx.accounts.redemption.closed_at == 0, ErrorCode::RedemptionClosed);
+        require!(now >= ctx.accounts.redemption.sweep_deadline, ErrorCode::SweepTooEarly);
+
+        let trailer_key = ctx.accounts.trailer_asset.key();
+        let seeds = &[b"redemption".as_ref(), trailer_key.as_ref(), &[ctx.accounts.redemption.bump]];
+        let signer = &[&seeds[..]];
+
+        let unclaimed = ctx.accounts.redemption_vault.amount;
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.redemption_vault.to_account_info(),
+            to: ctx.accounts.authority_payment_account.to_account_info(),
+            authority: ctx.accounts.redemption.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token::transfer(cpi_ctx, unclaimed)?;
+
+        ctx.accounts.redemption.closed_at = now;
+        let trailer_asset = &mut ctx.accounts.trailer_asset;
+        let previous = trailer_asset.status;
+        trailer_asset.set_status(AssetStatus::Redeemed, now);
+        emit!(StatusChanged {
+            trailer_asset: trailer_asset.key(),
+            from: previous,
+            to: AssetStatus::Redeemed,
+            at: now,
+        });
+
+        msg!("Redemption swept for trailer {}: {} unclaimed", trailer_asset.name, unclaimed);
+        Ok(())
+    }
+
+    /// Crea la configuración global del guardián. Solo la autoridad de
+    /// actualización del programa puede hacerlo y queda como admin
+    pub fn init_guardian(ctx: Context<InitGuardian>, guardian: Pubkey) -> Result<()> {
+        ctx.accounts.guardian_config.set_inner(GuardianConfig {
+            admin: ctx.accounts.admin.key(),
+            guardian,
+            pauses: Vec::new(),
+            bump: ctx.bumps.guardian_config,
+        });
+        msg!("Guardian config initialized, guardian: {}", guardian);
+        Ok(())
+    }
+
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+        ctx.accounts.guardian_config.guardian = guardian;
+        msg!("Guardian set to {}", guardian);
+        Ok(())
+    }
+
+    /// Pausa un programa entero (`instruction` todo ceros) o una instrucción por
+    /// su discriminador. La pausa vence a los `duration` segundos.
+    pub fn guardian_pause(
+        ctx: Context<GuardianAction>,
+        program: Pubkey,
+        instruction: [u8; 8],
+        duration: i64,
+    ) -> Result<()> {
+        require!(duration > 0 && duration <= MAX_PAUSE_DURATION, ErrorCode::InvalidPauseDuration);
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.guardian_config.pause(program, instruction, now + duration, now)?;
+
+        emit!(GuardianPaused {
+            program,
+            instruction,
+            expires_at: now + duration,
+        });
+        Ok(())
+    }
+
+    pub fn guardian_unpause(
+        ctx: Context<GuardianAction>,
+        program: Pubkey,
+        instruction: [u8; 8],
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.guardian_config.unpause(&program, &instruction, now);
+        msg!("Pause lifted for program {}", program);
+        Ok(())
+    }
+}
+
+/// PDA fijado como freeze authority en cada mint de trailer
+pub fn freeze_authority_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"freeze_authority", mint.as_ref()], &ID)
+}
+
+/// Vault de tokens del pool de retornos del trailer en returns_distribution
+pub fn pool_vault_address(trailer_asset: &Pubkey) -> Pubkey {
+    let (pool, _) = Pubkey::find_program_address(&[b"pool", trailer_asset.as_ref()], &RETURNS_DISTRIBUTION_ID);
+    Pubkey::find_program_address(&[b"pool_vault", pool.as_ref()], &RETURNS_DISTRIBUTION_ID).0
+}
+
+fn thaw<'info>(
+    account: &Account<'info, TokenAccount>,
+    mint: &Account<'info, Mint>,
+    freeze_authority: &UncheckedAccount<'info>,
+    bump: u8,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    let mint_key = mint.key();
+    let seeds = &[b"freeze_authority".as_ref(), mint_key.as_ref(), &[bump]];
+    let signer = &[&seeds[..]];
+
+    let cpi_accounts = ThawAccount {
+        account: account.to_account_info(),
+        mint: mint.to_account_info(),
+        authority: freeze_authority.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer);
+    token::thaw_account(cpi_ctx)
 }
 
 #[derive(Accounts)]
-pub struct CreateTrailerNFT<'info> {
+pub struct OpenRedemption<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
 
+    #[account(
+        mut,
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        has_one = authority
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
     #[account(
         init,
         payer = authority,
-        space = 8 + TrailerAsset::INIT_SPACE,
-        seeds = [b"trailer", mint.key().as_ref()],
+        space = 8 + Redemption::INIT_SPACE,
+        seeds = [b"redemption", trailer_asset.key().as_ref()],
         bump
     )]
-    pub trailer_asset: Account<'info, TrailerAsset>,
+    pub redemption: Account<'info, Redemption>,
 
-    /// CHECK: Esta es la cuenta mint que sera creada externamente
-    pub mint: Account<'info, Mint>,
+    /// Moneda en la que se pagan los fondos
+    pub payment_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        token::mint = payment_mint,
+        token::authority = redemption,
+        seeds = [b"redemption_vault", redemption.key().as_ref()],
+        bump
+    )]
+    pub redemption_vault: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateMetadata<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
+pub struct SetLiquidationApprover<'info> {
+    pub liquidation_approver: Signer<'info>,
 
     #[account(
         mut,
         seeds = [b"trailer", trailer_asset.mint.as_ref()],
         bump = trailer_asset.bump,
+        has_one = liquidation_approver
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct Liquidate<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub liquidation_approver: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        has_one = authority,
+        has_one = liquidation_approver,
+        constraint = trailer_asset.liquidation_approver != trailer_asset.authority @ ErrorCode::ApproverIsAuthority
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Redemption::INIT_SPACE,
+        seeds = [b"redemption", trailer_asset.key().as_ref()],
+        bump
+    )]
+    pub redemption: Account<'info, Redemption>,
+
+    pub payment_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        token::mint = payment_mint,
+        token::authority = redemption,
+        seeds = [b"redemption_vault", redemption.key().as_ref()],
+        bump
+    )]
+    pub redemption_vault: Account<'info, TokenAccount>,
+
+    #[account(mut, constraint = authority_payment_account.mint == payment_mint.key())]
+    pub authority_payment_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct DepositRedemptionProceeds<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        has_one = authority
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(
+        mut,
+        seeds = [b"redemption", trailer_asset.key().as_ref()],
+        bump = redemption.bump
+    )]
+    pub redemption: Account<'info, Redemption>,
+
+    #[account(mut, seeds = [b"redemption_vault", redemption.key().as_ref()], bump)]
+    pub redemption_vault: Account<'info, TokenAccount>,
+
+    #[account(mut, constraint = authority_payment_account.mint == redemption.payment_mint)]
+    pub authority_payment_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeRedemption<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        has_one = authority
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(
+        mut,
+        seeds = [b"redemption", trailer_asset.key().as_ref()],
+        bump = redemption.bump
+    )]
+    pub redemption: Account<'info, Redemption>,
+
+    #[account(seeds = [b"redemption_vault", redemption.key().as_ref()], bump)]
+    pub redemption_vault: Account<'info, TokenAccount>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct Redeem<'info> {
+    pub holder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        has_one = mint
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"redemption", trailer_asset.key().as_ref()],
+        bump = redemption.bump
+    )]
+    pub redemption: Account<'info, Redemption>,
+
+    #[account(mut, seeds = [b"redemption_vault", redemption.key().as_ref()], bump)]
+    pub redemption_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = holder_token_account.mint == mint.key(),
+        constraint = holder_token_account.owner == holder.key()
+    )]
+    pub holder_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, constraint = holder_payment_account.mint == redemption.payment_mint)]
+    pub holder_payment_account: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA que firma el descongelamiento de la cuenta del tenedor
+    #[account(seeds = [b"freeze_authority", mint.key().as_ref()], bump)]
+    pub freeze_authority: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct FreezeHolder<'info> {
+    pub lock_manager: Signer<'info>,
+
+    #[account(
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        has_one = mint,
+        constraint = trailer_asset.has_role(trailer_asset.roles.lock_manager, &lock_manager.key()) @ ErrorCode::Unauthorized
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut, constraint = holder_token_account.mint == mint.key())]
+    pub holder_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA que firma el congelamiento como freeze authority del mint
+    #[account(seeds = [b"freeze_authority", mint.key().as_ref()], bump)]
+    pub freeze_authority: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SweepRedemption<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        has_one = authority
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(
+        mut,
+        seeds = [b"redemption", trailer_asset.key().as_ref()],
+        bump = redemption.bump
+    )]
+    pub redemption: Account<'info, Redemption>,
+
+    #[account(mut, seeds = [b"redemption_vault", redemption.key().as_ref()], bump)]
+    pub redemption_vault: Account<'info, TokenAccount>,
+
+    #[account(mut, constraint = authority_payment_account.mint == redemption.payment_mint)]
+    pub authority_payment_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct InitSeries<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Series::INIT_SPACE,
+        seeds = [b"series", name.as_bytes()],
+        bump
+    )]
+    pub series: Account<'info, Series>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSeries<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"series", series.name.as_bytes()],
+        bump = series.bump,
+        has_one = authority
+    )]
+    pub series: Account<'info, Series>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String, symbol: String, uri: String, series: String)]
+pub struct CreateTrailerNFT<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"series", series.as_bytes()],
+        bump = series_account.bump,
         has_one = authority
     )]
+    pub series_account: Account<'info, Series>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + TrailerAsset::INIT_SPACE,
+        seeds = [b"trailer", mint.key().as_ref()],
+        bump
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    /// CHECK: Esta es la cuenta mint que sera creada externamente
+    pub mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMetadata<'info> {
+    #[account(mut)]
+    pub metadata_manager: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        constraint = trailer_asset.has_role(trailer_asset.roles.metadata_manager, &metadata_manager.key()) @ ErrorCode::Unauthorized
+    )]
     pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
 }
 
 #[derive(Accounts)]
 pub struct SetLockStatus<'info> {
     #[account(mut)]
+    pub lock_manager: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        constraint = trailer_asset.has_role(trailer_asset.roles.lock_manager, &lock_manager.key()) @ ErrorCode::Unauthorized
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct TransitionStatus<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        has_one = authority
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        has_one = authority
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    pub pending_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        has_one = pending_authority
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetRoles<'info> {
     pub authority: Signer<'info>,
 
     #[account(
-        mut,
+        mut,
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        has_one = authority
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RecordMaintenance<'info> {
+    #[account(mut)]
+    pub maintenance_recorder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        constraint = trailer_asset.has_role(trailer_asset.roles.maintenance_recorder, &maintenance_recorder.key()) @ ErrorCode::Unauthorized
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(
+        init_if_needed,
+        payer = maintenance_recorder,
+        space = 8 + MaintenanceLog::INIT_SPACE,
+        seeds = [
+            b"maintenance",
+            trailer_asset.key().as_ref(),
+            &(trailer_asset.maintenance_count / MAINTENANCE_PAGE_SIZE).to_le_bytes()
+        ],
+        bump
+    )]
+    pub log_page: Account<'info, MaintenanceLog>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetOracle<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        has_one = authority
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct PostTelematics<'info> {
+    #[account(mut)]
+    pub oracle: Signer<'info>,
+
+    #[account(
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        has_one = oracle
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(
+        init_if_needed,
+        payer = oracle,
+        space = 8 + Telematics::INIT_SPACE,
+        seeds = [b"telematics", trailer_asset.key().as_ref()],
+        bump
+    )]
+    pub telematics: Account<'info, Telematics>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CreateLease<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        has_one = authority
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Lease::INIT_SPACE,
+        seeds = [b"lease", trailer_asset.key().as_ref(), &trailer_asset.lease_count.to_le_bytes()],
+        bump
+    )]
+    pub lease: Account<'info, Lease>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetLeaseStatus<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        has_one = authority
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(
+        mut,
+        seeds = [b"lease", trailer_asset.key().as_ref(), &lease.lease_id.to_le_bytes()],
+        bump = lease.bump,
+        has_one = trailer_asset
+    )]
+    pub lease: Account<'info, Lease>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitGuardian<'info> {
+    /// Solo la autoridad de actualización del programa puede crear la configuración
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ ErrorCode::Unauthorized)]
+    pub program: Program<'info, crate::program::AssetNft>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + GuardianConfig::INIT_SPACE,
+        seeds = [b"guardian"],
+        bump
+    )]
+    pub guardian_config: Account<'info, GuardianConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(mut, seeds = [b"guardian"], bump = guardian_config.bump, has_one = admin)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct GuardianAction<'info> {
+    pub guardian: Signer<'info>,
+
+    #[account(mut, seeds = [b"guardian"], bump = guardian_config.bump, has_one = guardian)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AddPolicy<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        has_one = authority
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + InsurancePolicy::INIT_SPACE,
+        seeds = [b"policy", trailer_asset.key().as_ref(), &trailer_asset.policy_count.to_le_bytes()],
+        bump
+    )]
+    pub policy: Account<'info, InsurancePolicy>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct OpenClaim<'info> {
+    #[account(mut)]
+    pub lock_manager: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        constraint = trailer_asset.has_role(trailer_asset.roles.lock_manager, &lock_manager.key()) @ ErrorCode::Unauthorized
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(
+        mut,
+        seeds = [b"policy", trailer_asset.key().as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.bump,
+        has_one = trailer_asset
+    )]
+    pub policy: Account<'info, InsurancePolicy>,
+
+    #[account(
+        init,
+        payer = lock_manager,
+        space = 8 + InsuranceClaim::INIT_SPACE,
+        seeds = [b"claim", policy.key().as_ref(), &policy.claim_count.to_le_bytes()],
+        bump
+    )]
+    pub claim: Account<'info, InsuranceClaim>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SettleClaim<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        has_one = authority
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(
+        seeds = [b"policy", trailer_asset.key().as_ref(), &policy.policy_id.to_le_bytes()],
+        bump = policy.bump,
+        has_one = trailer_asset
+    )]
+    pub policy: Account<'info, InsurancePolicy>,
+
+    #[account(
+        mut,
+        seeds = [b"claim", policy.key().as_ref(), &claim.claim_id.to_le_bytes()],
+        bump = claim.bump,
+        has_one = policy
+    )]
+    pub claim: Account<'info, InsuranceClaim>,
+
+    #[account(constraint = insurer.key() == policy.insurer_wallet @ ErrorCode::Unauthorized)]
+    pub insurer: Signer<'info>,
+
+    #[account(mut, constraint = insurer_payment_account.owner == insurer.key())]
+    pub insurer_payment_account: Account<'info, TokenAccount>,
+
+    /// Vault del pool (`Holders`) o cuenta del taller (`Repair`)
+    #[account(mut, constraint = payee_account.mint == insurer_payment_account.mint)]
+    pub payee_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AddDocument<'info> {
+    #[account(mut)]
+    pub metadata_manager: Signer<'info>,
+
+    #[account(
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        constraint = trailer_asset.has_role(trailer_asset.roles.metadata_manager, &metadata_manager.key()) @ ErrorCode::Unauthorized
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(
+        init_if_needed,
+        payer = metadata_manager,
+        space = 8 + DocumentRegistry::INIT_SPACE,
+        seeds = [b"documents", trailer_asset.key().as_ref()],
+        bump
+    )]
+    pub registry: Account<'info, DocumentRegistry>,
+
+    #[account(
+        init_if_needed,
+        payer = metadata_manager,
+        space = 8 + DocumentPage::INIT_SPACE,
+        seeds = [
+            b"documents",
+            trailer_asset.key().as_ref(),
+            &(registry.document_count / DOCUMENT_PAGE_SIZE).to_le_bytes()
+        ],
+        bump
+    )]
+    pub page: Account<'info, DocumentPage>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct Appraise<'info> {
+    #[account(mut)]
+    pub appraiser: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        constraint = trailer_asset.has_role(trailer_asset.roles.appraiser, &appraiser.key()) @ ErrorCode::Unauthorized
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(
+        init_if_needed,
+        payer = appraiser,
+        space = 8 + ValuationHistory::INIT_SPACE,
+        seeds = [b"valuations", trailer_asset.key().as_ref()],
+        bump
+    )]
+    pub valuation_history: Account<'info, ValuationHistory>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct GetNav<'info> {
+    #[account(
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+}
+
+#[derive(Accounts)]
+pub struct SetDepreciation<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        has_one = authority
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct GetBookValue<'info> {
+    #[account(
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+}
+
+#[derive(Accounts)]
+pub struct SetMarketAuthority<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        has_one = authority
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTokensSold<'info> {
+    pub market_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        has_one = market_authority
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    #[account(
+        mut,
+        seeds = [b"series", trailer_asset.series.as_bytes()],
+        bump = series.bump
+    )]
+    pub series: Account<'info, Series>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct GetFundingStatus<'info> {
+    #[account(
         seeds = [b"trailer", trailer_asset.mint.as_ref()],
-        bump = trailer_asset.bump,
-        has_one = authority
+        bump = trailer_asset.bump
     )]
     pub trailer_asset: Account<'info, TrailerAsset>,
 }
@@ -137,4 +2040,1168 @@ pub struct TrailerAsset {
     pub created_at: i64,
     pub expiry_at: i64,
     pub bump: u8,
+    /// Firmante derivado autorizado a reportar ventas (el PDA de la venta del mercado primario)
+    pub market_authority: Pubkey,
+    pub fully_funded_at: i64,
+    pub status: AssetStatus,
+    /// Última entrada del activo en cada estado, indexada por `AssetStatus as usize`
+    pub status_entered_at: [i64; AssetStatus::COUNT],
+    /// Lo fija `propose_authority` y se limpia al aceptarse
+    pub pending_authority: Pubkey,
+    pub roles: AssetRoles,
+    /// PDA de gobernanza o multisig que debe cofirmar una liquidación
+    pub liquidation_approver: Pubkey,
+    pub lease_count: u32,
+    pub policy_count: u32,
+    pub maintenance_count: u32,
+    pub last_inspection_at: i64,
+    /// Firmante autorizado a publicar lecturas telemáticas
+    pub oracle: Pubkey,
+    /// Último valor tasado; `total_value` conserva la recaudación original
+    pub current_value: u64,
+    pub last_appraised_at: i64,
+    pub depreciation: DepreciationConfig,
+}
+
+/// Claves operativas que pueden rotar con independencia de la autoridad del activo
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct AssetRoles {
+    pub metadata_manager: Pubkey,
+    pub lock_manager: Pubkey,
+    pub maintenance_recorder: Pubkey,
+    pub appraiser: Pubkey,
+}
+
+impl AssetRoles {
+    pub fn all(key: Pubkey) -> Self {
+        Self {
+            metadata_manager: key,
+            lock_manager: key,
+            maintenance_recorder: key,
+            appraiser: key,
+        }
+    }
+
+    /// Pasa a `to` todos los roles de `from`, sin tocar los roles delegados
+    pub fn reassign(&mut self, from: &Pubkey, to: &Pubkey) {
+        for role in [
+            &mut self.metadata_manager,
+            &mut self.lock_manager,
+            &mut self.maintenance_recorder,
+            &mut self.appraiser,
+        ] {
+            if role == from {
+                *role = *to;
+            }
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum AssetStatus {
+    Fundraising,
+    Acquisition,
+    Operating,
+    Maintenance,
+    Idle,
+    ForSale,
+    Liquidated,
+    Redeemed,
+    /// Vencido, los tenedores queman sus unidades a cambio de su parte de los fondos
+    Redeeming,
+}
+
+impl AssetStatus {
+    pub const COUNT: usize = 9;
+
+    pub fn can_transition_to(self, next: AssetStatus) -> bool {
+        use AssetStatus::*;
+        matches!(
+            (self, next),
+            (Fundraising, Acquisition)
+                | (Acquisition, Operating)
+                | (Operating, Maintenance | Idle | ForSale)
+                | (Maintenance, Operating | Idle)
+                | (Idle, Operating | Maintenance | ForSale)
+                | (ForSale, Operating | Idle)
+                | (Fundraising | Acquisition | Operating | Maintenance | Idle | ForSale, Liquidated)
+        )
+    }
+
+    /// Los tenedores queman sus unidades a cambio de su parte de los fondos
+    pub fn is_winding_down(self) -> bool {
+        matches!(self, AssetStatus::Liquidated | AssetStatus::Redeeming)
+    }
+
+    /// El trailer superó la recaudación y no está en liquidación
+    pub fn is_operational(self) -> bool {
+        matches!(
+            self,
+            AssetStatus::Acquisition
+                | AssetStatus::Operating
+                | AssetStatus::Maintenance
+                | AssetStatus::Idle
+                | AssetStatus::ForSale
+        )
+    }
+
+    /// Los tokens fraccionados pueden negociarse en el mercado secundario
+    pub fn is_tradable(self) -> bool {
+        self.is_operational()
+    }
+
+    /// Se pueden distribuir retornos a los tenedores
+    pub fn accepts_distributions(self) -> bool {
+        self.is_operational()
+    }
+}
+
+/// Pago a prorrata de los fondos de un trailer contra tokens fraccionados quemados
+#[account]
+#[derive(InitSpace)]
+pub struct Redemption {
+    pub trailer_asset: Pubkey,
+    pub payment_mint: Pubkey,
+    pub kind: RedemptionKind,
+    pub total_proceeds: u64,
+    pub units_outstanding: u64,
+    pub units_redeemed: u64,
+    pub amount_paid: u64,
+    pub opened_at: i64,
+    pub sweep_deadline: i64,
+    /// Se fija cuando se rescatan todas las unidades o se barre el vault
+    pub closed_at: i64,
+    pub bump: u8,
+    /// Fijado cuando la autoridad declara los fondos definitivos; hasta
+    /// entonces no se puede rescatar
+    pub finalized_at: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum RedemptionKind {
+    Maturity,
+    Liquidation {
+        reason: LiquidationReason,
+        /// Hash del documento de respaldo (informe de pérdida, contrato de venta...)
+        reason_hash: [u8; 32],
+    },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum LiquidationReason {
+    TotalLoss,
+    EarlySale,
+    Regulatory,
+    Other,
+}
+
+#[event]
+pub struct TrailerLiquidated {
+    pub trailer_asset: Pubkey,
+    pub approver: Pubkey,
+    pub reason: LiquidationReason,
+    pub reason_hash: [u8; 32],
+    pub proceeds: u64,
+    pub at: i64,
+}
+
+impl Redemption {
+    /// Cierra los depósitos una vez que todos los fondos declarados están en el vault
+    pub fn finalize(&mut self, vault_amount: u64, now: i64) -> Result<()> {
+        require!(self.finalized_at == 0, ErrorCode::ProceedsFinalized);
+        require!(vault_amount >= self.total_proceeds, ErrorCode::ProceedsNotInVault);
+        self.finalized_at = now;
+        Ok(())
+    }
+
+    /// Parte de los fondos que quedan en el vault para `units` de las unidades aún no rescatadas
+    pub fn payout_for(&self, units: u64) -> Result<u64> {
+        require!(self.finalized_at != 0, ErrorCode::ProceedsNotFinal);
+        let remaining_units = self
+            .units_outstanding
+            .checked_sub(self.units_redeemed)
+            .ok_or(ErrorCode::ArithmeticError)?;
+        require!(units > 0 && units <= remaining_units, ErrorCode::ArithmeticError);
+        let remaining_proceeds = self
+            .total_proceeds
+            .checked_sub(self.amount_paid)
+            .ok_or(ErrorCode::ArithmeticError)? as u128;
+        // Nunca supera los fondos restantes, así que vuelve a caber en u64
+        Ok((remaining_proceeds * units as u128 / remaining_units as u128) as u64)
+    }
+}
+
+/// Una página del historial de mantenimiento de un trailer
+#[account]
+#[derive(InitSpace)]
+pub struct MaintenanceLog {
+    pub trailer_asset: Pubkey,
+    pub page: u32,
+    #[max_len(MAINTENANCE_PAGE_SIZE)]
+    pub records: Vec<MaintenanceRecord>,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct MaintenanceRecord {
+    pub performed_at: i64,
+    pub odometer_km: u64,
+    pub work_type: WorkType,
+    pub cost: u64,
+    #[max_len(32)]
+    pub inspector: String,
+    pub document_hash: [u8; 32],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum WorkType {
+    Inspection,
+    Preventive,
+    Repair,
+    Tires,
+    Brakes,
+    Other,
+}
+
+/// Última lectura telemática de un trailer
+#[account]
+#[derive(InitSpace)]
+pub struct Telematics {
+    pub trailer_asset: Pubkey,
+    pub latest: TelematicsSnapshot,
+    pub snapshot_count: u64,
+    pub bump: u8,
+}
+
+impl Telematics {
+    /// Proporción de la distancia recorrida con carga, en puntos básicos
+    pub fn loaded_bps(&self) -> u16 {
+        if self.latest.odometer_km == 0 {
+            return 0;
+        }
+        (self.latest.loaded_km.min(self.latest.odometer_km) as u128 * TrailerAsset::BPS_DENOMINATOR as u128
+            / self.latest.odometer_km as u128) as u16
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct TelematicsSnapshot {
+    pub observed_at: i64,
+    pub odometer_km: u64,
+    pub hours_in_service: u64,
+    pub loaded_km: u64,
+    /// Hash de la región aproximada en la que operó el trailer
+    pub region_hash: [u8; 32],
+}
+
+#[event]
+pub struct TelematicsPosted {
+    pub trailer_asset: Pubkey,
+    pub snapshot: TelematicsSnapshot,
+    /// Utilización histórica: proporción del odómetro recorrida con carga
+    pub loaded_bps: u16,
+}
+
+#[event]
+pub struct StatusChanged {
+    pub trailer_asset: Pubkey,
+    pub from: AssetStatus,
+    pub to: AssetStatus,
+    pub at: i64,
+}
+
+impl TrailerAsset {
+    pub const BPS_DENOMINATOR: u64 = 10_000;
+
+    pub fn set_status(&mut self, status: AssetStatus, now: i64) {
+        self.status = status;
+        self.status_entered_at[status as usize] = now;
+    }
+
+    /// La autoridad siempre puede actuar en nombre de cualquier rol operativo
+    pub fn has_role(&self, role: Pubkey, key: &Pubkey) -> bool {
+        *key == self.authority || *key == role
+    }
+
+    /// Valor neto del activo por token fraccionado, según la última tasación
+    pub fn nav_per_token(&self) -> u64 {
+        if self.total_tokens == 0 {
+            return 0;
+        }
+        self.current_value / self.total_tokens as u64
+    }
+
+    /// Valor contable en `now` según el plan de depreciación, partiendo de
+    /// `total_value` en `created_at` y sin bajar nunca del valor residual
+    pub fn book_value_at(&self, now: i64) -> u64 {
+        let config = &self.depreciation;
+        let cost = self.total_value as u128;
+        let residual = config.residual_value.min(self.total_value) as u128;
+        let term = self.term_years as i64 * SECONDS_PER_YEAR;
+        let elapsed = (now - self.created_at).clamp(0, term.max(0));
+        if term == 0 {
+            return residual as u64;
+        }
+
+        let value = match config.method {
+            DepreciationMethod::StraightLine => {
+                cost - (cost - residual) * elapsed as u128 / term as u128
+            }
+            DepreciationMethod::DecliningBalance => {
+                let rate = config.rate_bps as u128;
+                let denominator = TrailerAsset::BPS_DENOMINATOR as u128;
+                let mut value = cost;
+                for _ in 0..(elapsed / SECONDS_PER_YEAR) {
+                    value = value * (denominator - rate) / denominator;
+                }
+                // Prorratea linealmente la depreciación del año en curso
+                let into_year = (elapsed % SECONDS_PER_YEAR) as u128;
+                value - value * rate * into_year / (denominator * SECONDS_PER_YEAR as u128)
+            }
+        };
+        value.max(residual) as u64
+    }
+
+    pub fn funded_bps(&self) -> u16 {
+        if self.total_tokens == 0 {
+            return 0;
+        }
+        (self.tokens_sold as u64 * Self::BPS_DENOMINATOR / self.total_tokens as u64) as u16
+    }
+}
+
+/// Pausas de emergencia compartidas por todos los programas del protocolo. El
+/// guardián puede ser un multisig o un PDA de gobernanza.
+///
+/// Toda instrucción que modifica estado en asset_nft, primary_market,
+/// secondary_market, returns_distribution y opti_freight la comprueba al
+/// entrar, y las llamadas entre programas la reenvían para que también aplique
+/// la pausa del programa llamado. Quedan exentas las instrucciones del propio
+/// guardián (`init_guardian`, `set_guardian`, `guardian_pause`,
+/// `guardian_unpause`), para que una pausa siempre pueda levantarse, y las
+/// vistas de solo lectura (`get_nav`, `get_book_value`, `get_funding_status`).
+#[account]
+#[derive(InitSpace)]
+pub struct GuardianConfig {
+    pub admin: Pubkey,
+    pub guardian: Pubkey,
+    #[max_len(MAX_PAUSES)]
+    pub pauses: Vec<PauseEntry>,
+    pub bump: u8,
+}
+
+impl GuardianConfig {
+    /// Se llama al entrar en cada instrucción; falla mientras el programa o la
+    /// instrucción tengan una pausa vigente
+    pub fn check(&self, program: &Pubkey, discriminator: &[u8]) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(!self.is_paused(program, discriminator, now), ErrorCode::GuardianPaused);
+        Ok(())
+    }
+
+    pub fn is_paused(&self, program: &Pubkey, discriminator: &[u8], now: i64) -> bool {
+        self.pauses
+            .iter()
+            .any(|pause| pause.expires_at > now && pause.covers(program, discriminator))
+    }
+
+    /// Registra una pausa nueva. Un objetivo ya pausado no se puede extender y,
+    /// una vez levantada o vencida, no se puede volver a pausar hasta pasado
+    /// `PAUSE_COOLDOWN`; así el guardián no puede encadenar pausas indefinidamente.
+    pub fn pause(&mut self, program: Pubkey, instruction: [u8; 8], expires_at: i64, now: i64) -> Result<()> {
+        // Las entradas se conservan mientras dura su enfriamiento
+        self.pauses
+            .retain(|pause| pause.expires_at.saturating_add(PAUSE_COOLDOWN) > now);
+        if let Some(existing) = self.pauses.iter().find(|pause| pause.matches_entry(&program, &instruction)) {
+            require!(existing.expires_at <= now, ErrorCode::AlreadyPaused);
+            return err!(ErrorCode::PauseCooldown);
+        }
+        require!(self.pauses.len() < MAX_PAUSES, ErrorCode::RegistryFull);
+        self.pauses.push(PauseEntry {
+            program,
+            instruction,
+            expires_at,
+        });
+        Ok(())
+    }
+
+    /// Levanta una pausa activa; el enfriamiento empieza a contar desde ahora
+    pub fn unpause(&mut self, program: &Pubkey, instruction: &[u8; 8], now: i64) {
+        for pause in self.pauses.iter_mut() {
+            if pause.matches_entry(program, instruction) && pause.expires_at > now {
+                pause.expires_at = now;
+            }
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct PauseEntry {
+    pub program: Pubkey,
+    /// Discriminador de la instrucción, todo ceros para el programa entero
+    pub instruction: [u8; 8],
+    pub expires_at: i64,
+}
+
+impl PauseEntry {
+    pub fn covers(&self, program: &Pubkey, discriminator: &[u8]) -> bool {
+        self.program == *program && (self.instruction == [0; 8] || self.instruction[..] == *discriminator)
+    }
+
+    fn matches_entry(&self, program: &Pubkey, instruction: &[u8; 8]) -> bool {
+        self.program == *program && self.instruction == *instruction
+    }
+}
+
+#[event]
+pub struct GuardianPaused {
+    pub program: Pubkey,
+    pub instruction: [u8; 8],
+    pub expires_at: i64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Series {
+    pub authority: Pubkey,
+    #[max_len(50)]
+    pub name: String,
+    #[max_len(200)]
+    pub uri: String,
+    pub bounds: SeriesBounds,
+    /// Número máximo de trailers (0 = sin límite)
+    pub max_trailers: u32,
+    /// Valor total agregado máximo (0 = sin límite)
+    pub max_total_value: u64,
+    pub trailer_count: u32,
+    pub total_value: u64,
+    pub total_tokens: u64,
+    pub tokens_sold: u64,
+    pub status: SeriesStatus,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum SeriesStatus {
+    /// Acepta trailers nuevos
+    Open,
+    /// Sin trailers nuevos, los existentes siguen operando
+    Closed,
+    /// Todos los trailers de la serie fueron rescatados
+    Matured,
+}
+
+/// Límites que debe respetar cada trailer creado en una serie
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct SeriesBounds {
+    pub min_apy: u16,
+    pub max_apy: u16,
+    pub min_term_years: u8,
+    pub max_term_years: u8,
+    pub min_total_tokens: u16,
+    pub max_total_tokens: u16,
+}
+
+impl SeriesBounds {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.min_apy <= self.max_apy
+                && self.min_term_years <= self.max_term_years
+                && self.min_total_tokens <= self.max_total_tokens,
+            ErrorCode::InvalidBounds
+        );
+        Ok(())
+    }
+}
+
+/// Contrato de arrendamiento entre el trailer y un transportista
+#[account]
+#[derive(InitSpace)]
+pub struct Lease {
+    pub trailer_asset: Pubkey,
+    pub lease_id: u32,
+    /// Hash de la identidad off-chain del transportista
+    pub lessee_id: [u8; 32],
+    pub monthly_rate: u64,
+    pub start_at: i64,
+    pub end_at: i64,
+    pub deposit: u64,
+    pub status: LeaseStatus,
+    pub bump: u8,
+    /// Momento en que el contrato terminó o cayó en impago (0 si no)
+    pub ended_at: i64,
+}
+
+impl Lease {
+    /// Renta adeudada hasta `now`, facturada mensualmente por adelantado desde
+    /// `start_at`. Los contratos terminados o en impago dejan de devengar en `ended_at`.
+    pub fn expected_rent_at(&self, now: i64) -> u64 {
+        let now = if self.status.is_ended() { now.min(self.ended_at) } else { now };
+        if now < self.start_at {
+            return 0;
+        }
+        let term = self.end_at - self.start_at;
+        let total_months = (term + SECONDS_PER_MONTH - 1) / SECONDS_PER_MONTH;
+        let months_due = ((now - self.start_at) / SECONDS_PER_MONTH + 1).min(total_months);
+        self.monthly_rate.saturating_mul(months_due as u64)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum LeaseStatus {
+    Active,
+    Completed,
+    Terminated,
+    Defaulted,
+}
+
+impl LeaseStatus {
+    /// Un contrato terminado o en impago no vuelve atrás; uno completado aún
+    /// puede declararse en impago si quedó renta sin pagar
+    pub fn can_transition_to(self, next: LeaseStatus) -> bool {
+        use LeaseStatus::*;
+        matches!((self, next), (Active, Completed | Terminated | Defaulted) | (Completed, Defaulted))
+    }
+
+    /// Terminado antes de plazo: la renta deja de devengarse en `ended_at`
+    pub fn is_ended(self) -> bool {
+        matches!(self, LeaseStatus::Terminated | LeaseStatus::Defaulted)
+    }
+}
+
+/// Póliza de seguro que cubre el trailer
+#[account]
+#[derive(InitSpace)]
+pub struct InsurancePolicy {
+    pub trailer_asset: Pubkey,
+    pub policy_id: u32,
+    #[max_len(32)]
+    pub insurer: String,
+    pub coverage: u64,
+    pub premium: u64,
+    pub starts_at: i64,
+    pub expires_at: i64,
+    pub claim_count: u32,
+    pub bump: u8,
+    /// Wallet del asegurador que firma y paga las liquidaciones
+    pub insurer_wallet: Pubkey,
+}
+
+impl InsurancePolicy {
+    pub fn covers(&self, at: i64) -> bool {
+        at >= self.starts_at && at < self.expires_at
+    }
+}
+
+/// Siniestro presentado contra una póliza por un único incidente
+#[account]
+#[derive(InitSpace)]
+pub struct InsuranceClaim {
+    pub policy: Pubkey,
+    pub trailer_asset: Pubkey,
+    pub claim_id: u32,
+    /// Hash del informe off-chain del incidente
+    pub incident_hash: [u8; 32],
+    pub incident_at: i64,
+    pub opened_at: i64,
+    pub status: ClaimStatus,
+    pub settlement_amount: u64,
+    pub route: ProceedsRoute,
+    pub settled_at: i64,
+    pub bump: u8,
+    /// Dueño de la cuenta que cobró una liquidación `Repair`
+    pub repair_payee: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum ClaimStatus {
+    Open,
+    Settled,
+    Denied,
+}
+
+/// Destino de las indemnizaciones liquidadas
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum ProceedsRoute {
+    /// Se paga contra facturas de reparación
+    Repair,
+    /// Se deposita en el pool de distribución de retornos
+    Holders,
+}
+
+#[event]
+pub struct ClaimOpened {
+    pub trailer_asset: Pubkey,
+    pub claim: Pubkey,
+    pub incident_hash: [u8; 32],
+}
+
+#[event]
+pub struct ClaimSettled {
+    pub trailer_asset: Pubkey,
+    pub claim: Pubkey,
+    pub amount: u64,
+    pub route: ProceedsRoute,
+}
+
+/// Índice de los documentos legales que respaldan un trailer. Las entradas
+/// viven en `DocumentPage`s de `DOCUMENT_PAGE_SIZE`, como el historial de mantenimiento.
+#[account]
+#[derive(InitSpace)]
+pub struct DocumentRegistry {
+    pub trailer_asset: Pubkey,
+    pub document_count: u32,
+    /// Última versión de cada tipo de documento, indexada por `DocumentType as usize`
+    pub versions: [u16; DocumentType::COUNT],
+    /// Posición de esa última versión en el registro; su página es `index / DOCUMENT_PAGE_SIZE`
+    pub latest_index: [u32; DocumentType::COUNT],
+    pub bump: u8,
+}
+
+/// Una página del registro de documentos de solo anexado de un trailer
+#[account]
+#[derive(InitSpace)]
+pub struct DocumentPage {
+    pub trailer_asset: Pubkey,
+    pub page: u32,
+    #[max_len(DOCUMENT_PAGE_SIZE)]
+    pub entries: Vec<DocumentEntry>,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct DocumentEntry {
+    pub doc_type: DocumentType,
+    #[max_len(200)]
+    pub uri: String,
+    /// SHA-256 del contenido del documento
+    pub sha256: [u8; 32],
+    pub version: u16,
+    pub effective_at: i64,
+    pub recorded_at: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum DocumentType {
+    SpvAgreement,
+    Title,
+    InsuranceCertificate,
+    Lease,
+    Other,
+}
+
+impl DocumentType {
+    pub const COUNT: usize = 5;
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ValuationHistory {
+    pub trailer_asset: Pubkey,
+    #[max_len(VALUATION_HISTORY_LEN)]
+    pub entries: Vec<Valuation>,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct Valuation {
+    pub value: u64,
+    pub nav_per_token: u64,
+    pub source: ValuationSource,
+    pub document_hash: [u8; 32],
+    pub recorded_at: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum ValuationSource {
+    IndependentAppraiser,
+    MarketComparable,
+    Insurance,
+    Internal,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct DepreciationConfig {
+    pub method: DepreciationMethod,
+    pub residual_value: u64,
+    /// Tasa anual del saldo decreciente, en puntos básicos
+    pub rate_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum DepreciationMethod {
+    StraightLine,
+    DecliningBalance,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BookValue {
+    pub book_value: u64,
+    pub book_value_per_token: u64,
+    pub as_of: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct NavInfo {
+    pub current_value: u64,
+    pub nav_per_token: u64,
+    pub last_appraised_at: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct FundingStatus {
+    pub tokens_sold: u16,
+    pub total_tokens: u16,
+    pub funded_bps: u16,
+    pub fully_funded_at: i64,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Los tokens vendidos superan el total de tokens del trailer")]
+    ExceedsTotalTokens,
+    #[msg("El nombre está vacío o supera los 50 caracteres")]
+    NameTooLong,
+    #[msg("El símbolo supera los 10 caracteres")]
+    SymbolTooLong,
+    #[msg("La URI supera los 200 caracteres")]
+    UriTooLong,
+    #[msg("El nombre de la serie está vacío o supera los 50 caracteres")]
+    SeriesTooLong,
+    #[msg("Los límites de la serie son inconsistentes")]
+    InvalidBounds,
+    #[msg("El APY está fuera de los límites de la serie")]
+    ApyOutOfBounds,
+    #[msg("El plazo es cero o está fuera de los límites de la serie")]
+    TermOutOfBounds,
+    #[msg("El total de tokens es cero o está fuera de los límites de la serie")]
+    TotalTokensOutOfBounds,
+    #[msg("El precio del token debe ser mayor que cero")]
+    InvalidTokenPrice,
+    #[msg("El valor total debe ser el precio del token por el total de tokens")]
+    ValueMismatch,
+    #[msg("El mint debe tener 0 decimales")]
+    InvalidMintDecimals,
+    #[msg("La mint authority debe ser la autoridad del trailer")]
+    InvalidMintAuthority,
+    #[msg("Transición de estado no permitida")]
+    InvalidTransition,
+    #[msg("La fecha está en el futuro")]
+    FutureTimestamp,
+    #[msg("El inspector supera los 32 caracteres")]
+    InspectorTooLong,
+    #[msg("La lectura telemática es más antigua que la edad permitida o que la última lectura")]
+    StaleTelematics,
+    #[msg("Los contadores telemáticos no pueden disminuir")]
+    NonMonotonicTelematics,
+    #[msg("La valuación debe ser mayor que cero")]
+    InvalidValuation,
+    #[msg("El valor residual supera el valor total o la tasa está fuera de rango")]
+    InvalidDepreciation,
+    #[msg("El firmante no tiene el rol requerido")]
+    Unauthorized,
+    #[msg("La serie no admite trailers nuevos")]
+    SeriesNotOpen,
+    #[msg("Se alcanzó el tope de trailers o de valor total de la serie")]
+    SeriesCapReached,
+    #[msg("El trailer no llegó a su fecha de vencimiento")]
+    NotMatured,
+    #[msg("El plazo de barrido debe estar en el futuro")]
+    InvalidSweepDeadline,
+    #[msg("El rescate ya está cerrado")]
+    RedemptionClosed,
+    #[msg("El plazo de barrido no ha vencido")]
+    SweepTooEarly,
+    #[msg("Error aritmético")]
+    ArithmeticError,
+    #[msg("El registro de documentos está lleno")]
+    RegistryFull,
+    #[msg("La renta debe ser positiva y el contrato terminar después de empezar")]
+    InvalidLease,
+    #[msg("El nombre del asegurador está vacío o es demasiado largo")]
+    InsurerTooLong,
+    #[msg("La cobertura debe ser positiva, la póliza vencer después de empezar y tener asegurador")]
+    InvalidPolicy,
+    #[msg("La póliza no cubre la fecha del incidente")]
+    PolicyNotInForce,
+    #[msg("El siniestro no está abierto")]
+    ClaimNotOpen,
+    #[msg("La indemnización supera la cobertura de la póliza")]
+    ExceedsCoverage,
+    #[msg("La freeze authority del mint debe ser el PDA freeze authority del programa")]
+    InvalidFreezeAuthority,
+    #[msg("El trailer no está bloqueado")]
+    NotLocked,
+    #[msg("El trailer está bloqueado")]
+    AssetLocked,
+    #[msg("Duración de pausa fuera de rango")]
+    InvalidPauseDuration,
+    #[msg("Pausado por el guardián")]
+    GuardianPaused,
+    #[msg("La autoridad no puede ser la clave por defecto")]
+    InvalidAuthority,
+    #[msg("El objetivo ya está pausado")]
+    AlreadyPaused,
+    #[msg("El objetivo está en enfriamiento tras su última pausa")]
+    PauseCooldown,
+    #[msg("Los fondos del rescate aún no son definitivos")]
+    ProceedsNotFinal,
+    #[msg("Los fondos del rescate ya son definitivos")]
+    ProceedsFinalized,
+    #[msg("El vault no contiene todos los fondos del rescate")]
+    ProceedsNotInVault,
+    #[msg("El aprobador de la liquidación no puede ser la autoridad")]
+    ApproverIsAuthority,
+    #[msg("La cuenta de destino no corresponde a la ruta de la indemnización")]
+    InvalidPayee,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CREATED_AT: i64 = 1_700_000_000;
+
+    fn trailer(total_value: u64, term_years: u8, depreciation: DepreciationConfig) -> TrailerAsset {
+        TrailerAsset {
+            authority: Pubkey::default(),
+            mint: Pubkey::default(),
+            name: "Trailer".to_string(),
+            symbol: "TRL".to_string(),
+            uri: String::new(),
+            series: "S1".to_string(),
+            total_value,
+            token_price: total_value / 1000,
+            total_tokens: 1000,
+            tokens_sold: 0,
+            apy: 1200,
+            term_years,
+            is_locked: false,
+            created_at: CREATED_AT,
+            expiry_at: CREATED_AT + term_years as i64 * SECONDS_PER_YEAR,
+            bump: 0,
+            market_authority: Pubkey::default(),
+            fully_funded_at: 0,
+            status: AssetStatus::Operating,
+            status_entered_at: [0; AssetStatus::COUNT],
+            pending_authority: Pubkey::default(),
+            roles: AssetRoles::all(Pubkey::default()),
+            liquidation_approver: Pubkey::default(),
+            lease_count: 0,
+            policy_count: 0,
+            maintenance_count: 0,
+            last_inspection_at: 0,
+            oracle: Pubkey::default(),
+            current_value: total_value,
+            last_appraised_at: CREATED_AT,
+            depreciation,
+        }
+    }
+
+    #[test]
+    fn status_follows_the_lifecycle() {
+        use AssetStatus::*;
+        assert!(Fundraising.can_transition_to(Acquisition));
+        assert!(Acquisition.can_transition_to(Operating));
+        assert!(Operating.can_transition_to(Maintenance));
+        assert!(Maintenance.can_transition_to(Idle));
+        assert!(Idle.can_transition_to(ForSale));
+        assert!(ForSale.can_transition_to(Operating));
+
+        assert!(!Fundraising.can_transition_to(Operating));
+        assert!(!Operating.can_transition_to(Fundraising));
+        assert!(!Operating.can_transition_to(Operating));
+        assert!(!Maintenance.can_transition_to(ForSale));
+    }
+
+    #[test]
+    fn wound_down_statuses_are_terminal() {
+        use AssetStatus::*;
+        let all = [
+            Fundraising, Acquisition, Operating, Maintenance, Idle, ForSale, Liquidated, Redeemed, Redeeming,
+        ];
+        assert_eq!(all.len(), AssetStatus::COUNT);
+        for status in all {
+            let winding_down = matches!(status, Liquidated | Redeemed | Redeeming);
+            // Todo lo que no esté ya en liquidación puede liquidarse
+            assert_eq!(status.can_transition_to(Liquidated), !winding_down, "{:?}", status);
+            if winding_down {
+                for next in all {
+                    assert!(!status.can_transition_to(next), "{:?} -> {:?}", status, next);
+                }
+            }
+        }
+        // El vencimiento pasa por `open_redemption`, nunca por una transición simple
+        assert!(!Operating.can_transition_to(Redeeming));
+        assert!(!Operating.can_transition_to(Redeemed));
+    }
+
+    #[test]
+    fn reassign_moves_only_roles_held_by_the_old_key() {
+        let old = Pubkey::new_unique();
+        let new = Pubkey::new_unique();
+        let appraiser = Pubkey::new_unique();
+        let mut roles = AssetRoles::all(old);
+        roles.appraiser = appraiser;
+
+        roles.reassign(&old, &new);
+        assert_eq!(roles.metadata_manager, new);
+        assert_eq!(roles.lock_manager, new);
+        assert_eq!(roles.maintenance_recorder, new);
+        assert_eq!(roles.appraiser, appraiser);
+    }
+
+    #[test]
+    fn straight_line_book_value() {
+        let asset = trailer(
+            100_000,
+            5,
+            DepreciationConfig {
+                method: DepreciationMethod::StraightLine,
+                residual_value: 20_000,
+                rate_bps: 0,
+            },
+        );
+        assert_eq!(asset.book_value_at(CREATED_AT - 1), 100_000);
+        assert_eq!(asset.book_value_at(CREATED_AT), 100_000);
+        // 80_000 depreciables en 5 años: 16_000 por año
+        assert_eq!(asset.book_value_at(CREATED_AT + SECONDS_PER_YEAR), 84_000);
+        assert_eq!(asset.book_value_at(CREATED_AT + SECONDS_PER_YEAR / 2), 92_000);
+        assert_eq!(asset.book_value_at(CREATED_AT + 5 * SECONDS_PER_YEAR), 20_000);
+        assert_eq!(asset.book_value_at(CREATED_AT + 50 * SECONDS_PER_YEAR), 20_000);
+    }
+
+    #[test]
+    fn declining_balance_book_value() {
+        let asset = trailer(
+            100_000,
+            5,
+            DepreciationConfig {
+                method: DepreciationMethod::DecliningBalance,
+                residual_value: 50_000,
+                rate_bps: 2_000,
+            },
+        );
+        assert_eq!(asset.book_value_at(CREATED_AT + SECONDS_PER_YEAR), 80_000);
+        // La mitad del cargo de 16_000 del segundo año
+        assert_eq!(asset.book_value_at(CREATED_AT + SECONDS_PER_YEAR * 3 / 2), 72_000);
+        assert_eq!(asset.book_value_at(CREATED_AT + 2 * SECONDS_PER_YEAR), 64_000);
+        // 51_200 tras 3 años, luego limitado por el valor residual
+        assert_eq!(asset.book_value_at(CREATED_AT + 3 * SECONDS_PER_YEAR), 51_200);
+        assert_eq!(asset.book_value_at(CREATED_AT + 4 * SECONDS_PER_YEAR), 50_000);
+    }
+
+    #[test]
+    fn residual_above_cost_is_capped() {
+        let asset = trailer(
+            100_000,
+            5,
+            DepreciationConfig {
+                method: DepreciationMethod::StraightLine,
+                residual_value: 150_000,
+                rate_bps: 0,
+            },
+        );
+        assert_eq!(asset.book_value_at(CREATED_AT + SECONDS_PER_YEAR), 100_000);
+    }
+
+    fn redemption(total_proceeds: u64, units_outstanding: u64) -> Redemption {
+        Redemption {
+            trailer_asset: Pubkey::default(),
+            payment_mint: Pubkey::default(),
+            kind: RedemptionKind::Maturity,
+            total_proceeds,
+            units_outstanding,
+            units_redeemed: 0,
+            amount_paid: 0,
+            opened_at: CREATED_AT,
+            sweep_deadline: CREATED_AT + SECONDS_PER_YEAR,
+            closed_at: 0,
+            bump: 0,
+            finalized_at: CREATED_AT,
+        }
+    }
+
+    #[test]
+    fn payout_is_pro_rata_and_drains_the_vault() {
+        let mut redemption = redemption(1_000_003, 3);
+        let mut paid = 0;
+        for _ in 0..3 {
+            let payout = redemption.payout_for(1).unwrap();
+            redemption.units_redeemed += 1;
+            redemption.amount_paid += payout;
+            paid += payout;
+        }
+        // El resto del redondeo va al último tenedor en vez de quedar en el vault
+        assert_eq!(paid, 1_000_003);
+        assert!(redemption.payout_for(1).is_err());
+    }
+
+    #[test]
+    fn payout_waits_for_final_proceeds_in_the_vault() {
+        let mut redemption = redemption(600, 6);
+        redemption.finalized_at = 0;
+        // Nada se paga (ni se quema) mientras los fondos pueden crecer
+        assert!(redemption.payout_for(2).is_err());
+
+        assert!(redemption.finalize(599, CREATED_AT).is_err());
+        redemption.finalize(600, CREATED_AT).unwrap();
+        assert!(redemption.finalize(600, CREATED_AT).is_err());
+        assert_eq!(redemption.payout_for(2).unwrap(), 200);
+    }
+
+    #[test]
+    fn payout_rejects_invalid_units_and_inconsistent_state() {
+        let mut redemption = redemption(1_000, 10);
+        assert!(redemption.payout_for(0).is_err());
+        assert!(redemption.payout_for(11).is_err());
+
+        redemption.units_redeemed = 11;
+        assert!(redemption.payout_for(1).is_err());
+
+        redemption.units_redeemed = 0;
+        redemption.amount_paid = 1_001;
+        assert!(redemption.payout_for(1).is_err());
+    }
+
+    fn lease(status: LeaseStatus, ended_at: i64) -> Lease {
+        Lease {
+            trailer_asset: Pubkey::default(),
+            lease_id: 0,
+            lessee_id: [0; 32],
+            monthly_rate: 1_000,
+            start_at: CREATED_AT,
+            end_at: CREATED_AT + 12 * SECONDS_PER_MONTH,
+            deposit: 0,
+            status,
+            bump: 0,
+            ended_at,
+        }
+    }
+
+    #[test]
+    fn rent_is_billed_monthly_in_advance() {
+        let active = lease(LeaseStatus::Active, 0);
+        assert_eq!(active.expected_rent_at(CREATED_AT - 1), 0);
+        assert_eq!(active.expected_rent_at(CREATED_AT), 1_000);
+        assert_eq!(active.expected_rent_at(CREATED_AT + SECONDS_PER_MONTH - 1), 1_000);
+        assert_eq!(active.expected_rent_at(CREATED_AT + SECONDS_PER_MONTH), 2_000);
+        // Limitado al plazo completo
+        assert_eq!(active.expected_rent_at(CREATED_AT + 100 * SECONDS_PER_MONTH), 12_000);
+    }
+
+    #[test]
+    fn rent_stops_accruing_when_the_lease_ends_early() {
+        let ended_at = CREATED_AT + 2 * SECONDS_PER_MONTH + 10;
+        for status in [LeaseStatus::Terminated, LeaseStatus::Defaulted] {
+            let ended = lease(status, ended_at);
+            assert_eq!(ended.expected_rent_at(CREATED_AT + SECONDS_PER_MONTH), 2_000);
+            assert_eq!(ended.expected_rent_at(ended_at), 3_000);
+            assert_eq!(ended.expected_rent_at(CREATED_AT + 11 * SECONDS_PER_MONTH), 3_000);
+        }
+    }
+
+    #[test]
+    fn ended_leases_never_change_status_again() {
+        use LeaseStatus::*;
+        for from in [Terminated, Defaulted] {
+            for to in [Active, Completed, Terminated, Defaulted] {
+                assert!(!from.can_transition_to(to));
+            }
+        }
+        assert!(!Completed.can_transition_to(Active));
+        assert!(Completed.can_transition_to(Defaulted));
+        assert!(Active.can_transition_to(Terminated));
+        assert!(!Active.can_transition_to(Active));
+    }
+
+    #[test]
+    fn loaded_share_is_capped_by_odometer() {
+        let mut telematics = Telematics {
+            trailer_asset: Pubkey::default(),
+            latest: TelematicsSnapshot::default(),
+            snapshot_count: 0,
+            bump: 0,
+        };
+        assert_eq!(telematics.loaded_bps(), 0);
+
+        telematics.latest.odometer_km = 80_000;
+        telematics.latest.loaded_km = 60_000;
+        assert_eq!(telematics.loaded_bps(), 7_500);
+
+        telematics.latest.loaded_km = 90_000;
+        assert_eq!(telematics.loaded_bps(), 10_000);
+    }
+
+    fn guardian(pauses: Vec<PauseEntry>) -> GuardianConfig {
+        GuardianConfig {
+            admin: Pubkey::default(),
+            guardian: Pubkey::default(),
+            pauses,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn guardian_pause_lapses_at_expiry() {
+        let program = Pubkey::new_from_array([1; 32]);
+        let config = guardian(vec![PauseEntry {
+            program,
+            instruction: [0; 8],
+            expires_at: CREATED_AT + 60,
+        }]);
+        let any_ix = crate::instruction::Redeem::DISCRIMINATOR;
+        assert!(config.is_paused(&program, any_ix, CREATED_AT));
+        assert!(config.is_paused(&program, any_ix, CREATED_AT + 59));
+        assert!(!config.is_paused(&program, any_ix, CREATED_AT + 60));
+        assert!(!config.is_paused(&Pubkey::new_from_array([2; 32]), any_ix, CREATED_AT));
+    }
+
+    #[test]
+    fn guardian_instruction_pause_only_covers_that_instruction() {
+        let mut instruction = [0; 8];
+        instruction.copy_from_slice(crate::instruction::Redeem::DISCRIMINATOR);
+        let config = guardian(vec![PauseEntry {
+            program: crate::ID,
+            instruction,
+            expires_at: CREATED_AT + 60,
+        }]);
+        assert!(config.is_paused(&crate::ID, crate::instruction::Redeem::DISCRIMINATOR, CREATED_AT));
+        assert!(!config.is_paused(&crate::ID, crate::instruction::SweepRedemption::DISCRIMINATOR, CREATED_AT));
+    }
+
+    #[test]
+    fn guardian_cannot_extend_or_chain_pauses() {
+        let program = Pubkey::new_from_array([1; 32]);
+        let whole = [0; 8];
+        let mut config = guardian(Vec::new());
+        let now = CREATED_AT;
+
+        config.pause(program, whole, now + MAX_PAUSE_DURATION, now).unwrap();
+        // Re-pausar un objetivo activo no extiende la pausa
+        assert!(config.pause(program, whole, now + 2 * MAX_PAUSE_DURATION, now + 1).is_err());
+
+        // Vencida, sigue bloqueada durante el enfriamiento
+        let expired = now + MAX_PAUSE_DURATION;
+        assert!(!config.is_paused(&program, &whole, expired));
+        assert!(config.pause(program, whole, expired + 60, expired + PAUSE_COOLDOWN - 1).is_err());
+        config.pause(program, whole, expired + PAUSE_COOLDOWN + 60, expired + PAUSE_COOLDOWN).unwrap();
+        assert!(config.is_paused(&program, &whole, expired + PAUSE_COOLDOWN));
+    }
+
+    #[test]
+    fn guardian_unpause_starts_the_cooldown() {
+        let program = Pubkey::new_from_array([1; 32]);
+        let whole = [0; 8];
+        let mut config = guardian(Vec::new());
+
+        config.pause(program, whole, CREATED_AT + 600, CREATED_AT).unwrap();
+        config.unpause(&program, &whole, CREATED_AT + 10);
+        assert!(!config.is_paused(&program, &whole, CREATED_AT + 10));
+        assert!(config.pause(program, whole, CREATED_AT + 600, CREATED_AT + 11).is_err());
+        config
+            .pause(program, whole, CREATED_AT + PAUSE_COOLDOWN + 600, CREATED_AT + 10 + PAUSE_COOLDOWN)
+            .unwrap();
+    }
 }
@@ -1,16 +1,1395 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{self, MintTo, TokenAccount, Transfer};
+use asset_nft::program::AssetNft;
+use asset_nft::{AssetStatus, GuardianConfig, LiquidationReason, Series, TrailerAsset};
+use primary_market::program::PrimaryMarket;
+use primary_market::Sale;
+use returns_distribution::program::ReturnsDistribution;
+use returns_distribution::Pool;
+use solana_sha256_hasher::hashv;
 
-declare_id!("GOVERNcaWJxH5YM3p8k6mwUhY7LzASwYxNzmgXzD4z");
+declare_id!("GvRNcaWJxH5YM3p8k6mwUhY7LzASwYxNzmgXzD4zzzz");
+
+const MAX_TITLE_LEN: usize = 64;
+const MIN_VOTING_PERIOD: i64 = 24 * 60 * 60;
+const MAX_VOTING_PERIOD: i64 = 30 * 24 * 60 * 60;
+const MAX_TIMELOCK: i64 = 30 * 24 * 60 * 60;
+const MAX_SNAPSHOT_WINDOW: i64 = 7 * 24 * 60 * 60;
+const BPS_DENOMINATOR: u64 = 10_000;
+pub const MAX_MEMO_LEN: usize = 64;
 
 #[program]
 pub mod governance {
     use super::*;
 
-    pub fn initialize(_ctx: Context<Initialize>) -> Result<()> {
-        msg!("Governance initialized");
+    /// Creates the governance account for a trailer or a series. Only the
+    /// asset authority can set it up.
+    pub fn init_governance(
+        ctx: Context<InitGovernance>,
+        scope: GovernanceScope,
+        voting_period: i64,
+        config: GovernanceConfig,
+    ) -> Result<()> {
+        require!(
+            (MIN_VOTING_PERIOD..=MAX_VOTING_PERIOD).contains(&voting_period),
+            GovernanceError::InvalidVotingPeriod
+        );
+        config.validate()?;
+
+        let target = &ctx.accounts.target;
+        let data = target.try_borrow_data()?;
+        let target_authority = match scope {
+            GovernanceScope::Trailer => TrailerAsset::try_deserialize(&mut &data[..])?.authority,
+            GovernanceScope::Series => Series::try_deserialize(&mut &data[..])?.authority,
+        };
+        require_keys_eq!(target_authority, ctx.accounts.authority.key(), GovernanceError::Unauthorized);
+
+        ctx.accounts.governance.set_inner(Governance {
+            scope,
+            target: target.key(),
+            voting_period,
+            config,
+            proposal_count: 0,
+            bump: ctx.bumps.governance,
+        });
+
+        msg!("Governance initialized for {:?} {}", scope, target.key());
+        Ok(())
+    }
+
+    /// Creates a proposal and checkpoints the current slot. Voting opens once
+    /// the snapshot authority posts the balances as of that slot. Remaining
+    /// accounts are `(trailer_asset, token_account)` pairs proving the proposer
+    /// holds tokens in scope.
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        title: String,
+        description_hash: [u8; 32],
+        action: ProposalAction,
+    ) -> Result<()> {
+        require!(!title.is_empty() && title.len() <= MAX_TITLE_LEN, GovernanceError::TitleTooLong);
+
+        let governance = &mut ctx.accounts.governance;
+        let weight = voter_weight(governance, &ctx.accounts.proposer.key(), ctx.remaining_accounts)?;
+        require!(weight > 0, GovernanceError::NoVotingPower);
+        match &action {
+            ProposalAction::UpdateConfig { config } => config.validate()?,
+            ProposalAction::Spend { amount, memo, .. } => require!(
+                *amount > 0 && memo.len() <= MAX_MEMO_LEN,
+                GovernanceError::InvalidSpend
+            ),
+            ProposalAction::ExpandFleet { .. } => require!(
+                governance.scope == GovernanceScope::Series,
+                GovernanceError::OutOfScope
+            ),
+            ProposalAction::TransitionStatus { new_status } => require!(
+                *new_status != AssetStatus::Liquidated,
+                GovernanceError::InvalidTransition
+            ),
+            _ => {}
+        }
+
+        let clock = Clock::get()?;
+        ctx.accounts.proposal.set_inner(Proposal {
+            governance: governance.key(),
+            proposal_id: governance.proposal_count,
+            proposer: ctx.accounts.proposer.key(),
+            title,
+            description_hash,
+            action,
+            status: ProposalStatus::Pending,
+            snapshot_slot: clock.slot,
+            snapshot_root: [0; 32],
+            total_weight: 0,
+            created_at: clock.unix_timestamp,
+            voting_starts_at: 0,
+            voting_ends_at: 0,
+            for_votes: 0,
+            against_votes: 0,
+            abstain_votes: 0,
+            executable_at: 0,
+            executed_at: 0,
+            bump: ctx.bumps.proposal,
+        });
+        governance.proposal_count += 1;
+
+        emit!(ProposalCreated {
+            governance: governance.key(),
+            proposal: ctx.accounts.proposal.key(),
+            proposer: ctx.accounts.proposer.key(),
+            snapshot_slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    /// Posts the merkle root of `(voter, weight)` leaves for the balances held
+    /// at the proposal's snapshot slot and opens the voting window. The root
+    /// must arrive within the snapshot window after the proposal was created.
+    pub fn post_snapshot(
+        ctx: Context<PostSnapshot>,
+        snapshot_root: [u8; 32],
+        total_weight: u64,
+    ) -> Result<()> {
+        require!(total_weight > 0, GovernanceError::InvalidSnapshot);
+        let proposal = &mut ctx.accounts.proposal;
+        require!(proposal.status == ProposalStatus::Pending, GovernanceError::SnapshotPosted);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            !proposal.snapshot_expired(&ctx.accounts.governance.config, now),
+            GovernanceError::SnapshotWindowClosed
+        );
+        proposal.snapshot_root = snapshot_root;
+        proposal.total_weight = total_weight;
+        proposal.status = ProposalStatus::Voting;
+        proposal.voting_starts_at = now;
+        proposal.voting_ends_at = now + ctx.accounts.governance.voting_period;
+
+        msg!("Snapshot posted for proposal {}: total weight {}", proposal.proposal_id, total_weight);
+        Ok(())
+    }
+
+    /// Closes a proposal that is still waiting for its snapshot. The proposer
+    /// can withdraw it at any time; anyone can expire it once the snapshot
+    /// window has passed.
+    pub fn expire_proposal(ctx: Context<ExpireProposal>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let proposal = &mut ctx.accounts.proposal;
+        require!(proposal.status == ProposalStatus::Pending, GovernanceError::SnapshotPosted);
+        require!(
+            ctx.accounts.caller.key() == proposal.proposer
+                || proposal.snapshot_expired(&ctx.accounts.governance.config, now),
+            GovernanceError::SnapshotWindowOpen
+        );
+
+        proposal.status = ProposalStatus::Expired;
+        msg!("Proposal {} expired without a snapshot", proposal.proposal_id);
+        Ok(())
+    }
+
+    /// Casts a vote with the weight the voter held at the snapshot, proven
+    /// against the snapshot root. A wallet votes once; if its delegate already
+    /// voted for it, the direct vote replaces the delegated one.
+    pub fn cast_vote(
+        ctx: Context<CastVote>,
+        choice: VoteChoice,
+        weight: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let proposal = &mut ctx.accounts.proposal;
+        require!(
+            proposal.status == ProposalStatus::Voting && proposal.is_voting(now),
+            GovernanceError::VotingClosed
+        );
+
+        let voter = ctx.accounts.voter.key();
+        require!(weight > 0, GovernanceError::NoVotingPower);
+        require!(proposal.verify_weight(&voter, weight, &proof), GovernanceError::InvalidProof);
+
+        let vote_record = &mut ctx.accounts.vote_record;
+        if vote_record.voter != Pubkey::default() {
+            require_keys_neq!(vote_record.cast_by, voter, GovernanceError::AlreadyVoted);
+            proposal.untally(vote_record.choice, vote_record.weight)?;
+        }
+        proposal.tally(choice, weight)?;
+
+        vote_record.set_inner(VoteRecord {
+            proposal: proposal.key(),
+            voter,
+            cast_by: voter,
+            choice,
+            weight,
+            voted_at: now,
+            bump: ctx.bumps.vote_record,
+        });
+
+        emit!(VoteCast {
+            proposal: proposal.key(),
+            voter,
+            cast_by: voter,
+            choice,
+            weight,
+        });
+        Ok(())
+    }
+
+    /// Delegates the caller's voting power under this governance to another
+    /// wallet until revoked
+    pub fn delegate(ctx: Context<Delegate>, delegate: Pubkey) -> Result<()> {
+        require_keys_neq!(delegate, ctx.accounts.delegator.key(), GovernanceError::InvalidDelegate);
+        ctx.accounts.delegation.set_inner(Delegation {
+            governance: ctx.accounts.governance.key(),
+            delegator: ctx.accounts.delegator.key(),
+            delegate,
+            created_at: Clock::get()?.unix_timestamp,
+            bump: ctx.bumps.delegation,
+        });
+        msg!("Voting power delegated to {}", delegate);
+        Ok(())
+    }
+
+    /// Revokes the delegation; votes already cast by the delegate stand unless
+    /// the delegator overrides them with `cast_vote`
+    pub fn revoke_delegation(ctx: Context<RevokeDelegation>) -> Result<()> {
+        msg!("Delegation to {} revoked", ctx.accounts.delegation.delegate);
+        Ok(())
+    }
+
+    /// Votes on behalf of one delegator with the delegator's snapshot weight
+    pub fn cast_delegated_vote(
+        ctx: Context<CastDelegatedVote>,
+        choice: VoteChoice,
+        weight: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let proposal = &mut ctx.accounts.proposal;
+        require!(
+            proposal.status == ProposalStatus::Voting && proposal.is_voting(now),
+            GovernanceError::VotingClosed
+        );
+
+        let delegator = ctx.accounts.delegation.delegator;
+        require!(weight > 0, GovernanceError::NoVotingPower);
+        require!(proposal.verify_weight(&delegator, weight, &proof), GovernanceError::InvalidProof);
+        proposal.tally(choice, weight)?;
+
+        ctx.accounts.vote_record.set_inner(VoteRecord {
+            proposal: proposal.key(),
+            voter: delegator,
+            cast_by: ctx.accounts.delegate.key(),
+            choice,
+            weight,
+            voted_at: now,
+            bump: ctx.bumps.vote_record,
+        });
+
+        emit!(VoteCast {
+            proposal: proposal.key(),
+            voter: delegator,
+            cast_by: ctx.accounts.delegate.key(),
+            choice,
+            weight,
+        });
         Ok(())
     }
+
+    /// Closes voting once the window has ended and queues the proposal behind
+    /// the timelock if it reached quorum and threshold; anyone can crank it
+    pub fn finalize_proposal(ctx: Context<FinalizeProposal>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let proposal = &mut ctx.accounts.proposal;
+        require!(proposal.status == ProposalStatus::Voting, GovernanceError::VotingClosed);
+        require!(now >= proposal.voting_ends_at, GovernanceError::VotingOpen);
+
+        let config = &ctx.accounts.governance.config;
+        if proposal.passed(config) {
+            proposal.status = ProposalStatus::Succeeded;
+            proposal.executable_at = now + config.timelock;
+        } else {
+            proposal.status = ProposalStatus::Defeated;
+        }
+
+        msg!("Proposal {} finalized: {:?}", proposal.proposal_id, proposal.status);
+        Ok(())
+    }
+
+    /// Executes a succeeded proposal after its timelock, with the treasury
+    /// PDA signing every CPI as the governance authority. Remaining accounts
+    /// are the ones the target instruction expects, in order, minus the
+    /// treasury signer:
+    /// - `SetLockStatus` / `TransitionStatus`: trailer_asset
+    /// - `ApproveLiquidation`: trailer_asset, redemption, payment_mint,
+    ///   redemption_vault, authority_payment_account, token_program,
+    ///   system_program (the executor must be the trailer authority unless
+    ///   the treasury owns the trailer)
+    /// - `PauseSale` / `ResumeSale`: sale, trailer_asset
+    /// - `SetPoolApy`: pool, trailer_asset
+    /// - `Spend` in SOL: recipient
+    /// - `Spend` in SPL: treasury_token_account, recipient_token_account,
+    ///   token_program
+    /// - `ExpandFleet`: trailer_asset, series_account, mint, sale,
+    ///   payment_mint, seller_token_account, payment_vault, token_vault,
+    ///   token_program, rent
+    pub fn execute_proposal<'info>(ctx: Context<'_, '_, 'info, 'info, ExecuteProposal<'info>>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let proposal = &ctx.accounts.proposal;
+        require!(proposal.status == ProposalStatus::Succeeded, GovernanceError::NotExecutable);
+        require!(now >= proposal.executable_at, GovernanceError::TimelockActive);
+
+        let governance = &ctx.accounts.governance;
+        let treasury = ctx.accounts.treasury.to_account_info();
+        let guardian_info = ctx.accounts.guardian_config.to_account_info();
+        let governance_key = governance.key();
+        let treasury_seeds = &[b"treasury".as_ref(), governance_key.as_ref(), &[ctx.bumps.treasury]];
+        let treasury_signer = &[&treasury_seeds[..]];
+        let accounts = ctx.remaining_accounts;
+
+        match proposal.action.clone() {
+            ProposalAction::Signal => {}
+            ProposalAction::UpdateConfig { config } => {
+                ctx.accounts.governance.config = config;
+            }
+            ProposalAction::SetLockStatus { is_locked } => {
+                require!(!accounts.is_empty(), GovernanceError::MissingAccounts);
+                require_trailer_in_scope(governance, &accounts[0])?;
+                let cpi_accounts = asset_nft::cpi::accounts::SetLockStatus {
+                    lock_manager: treasury,
+                    trailer_asset: accounts[0].clone(),
+                    guardian_config: guardian_info,
+                };
+                let cpi_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.asset_nft_program.to_account_info(),
+                    cpi_accounts,
+                    treasury_signer,
+                );
+                asset_nft::cpi::set_lock_status(cpi_ctx, is_locked)?;
+            }
+            ProposalAction::ApproveLiquidation { reason, reason_hash, proceeds, sweep_deadline } => {
+                require!(accounts.len() >= 7, GovernanceError::MissingAccounts);
+                let trailer_asset = require_trailer_in_scope(governance, &accounts[0])?;
+                // Trailers the treasury owns (fleet expansions) are liquidated
+                // with the treasury paying in and the governance PDA approving,
+                // since asset_nft rejects an approver that is the authority;
+                // others need their authority and the treasury as approver
+                let (authority, liquidation_approver) = if trailer_asset.authority == treasury.key() {
+                    (treasury, governance.to_account_info())
+                } else {
+                    (ctx.accounts.executor.to_account_info(), treasury)
+                };
+                let target = governance.target;
+                let governance_seeds = &[b"governance".as_ref(), target.as_ref(), &[governance.bump]];
+                let signers = &[&treasury_seeds[..], &governance_seeds[..]];
+                let cpi_accounts = asset_nft::cpi::accounts::Liquidate {
+                    authority,
+                    liquidation_approver,
+                    trailer_asset: accounts[0].clone(),
+                    redemption: accounts[1].clone(),
+                    payment_mint: accounts[2].clone(),
+                    redemption_vault: accounts[3].clone(),
+                    authority_payment_account: accounts[4].clone(),
+                    token_program: accounts[5].clone(),
+                    system_program: accounts[6].clone(),
+                    guardian_config: guardian_info,
+                };
+                let cpi_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.asset_nft_program.to_account_info(),
+                    cpi_accounts,
+                    signers,
+                );
+                asset_nft::cpi::liquidate(cpi_ctx, reason, reason_hash, proceeds, sweep_deadline)?;
+            }
+            ProposalAction::PauseSale | ProposalAction::ResumeSale => {
+                require!(accounts.len() >= 2, GovernanceError::MissingAccounts);
+                require_sale_in_scope(governance, &accounts[0], &accounts[1])?;
+                let cpi_accounts = primary_market::cpi::accounts::SetPause {
+                    authority: treasury,
+                    sale: accounts[0].clone(),
+                    guardian_config: guardian_info,
+                };
+                let cpi_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.primary_market_program.to_account_info(),
+                    cpi_accounts,
+                    treasury_signer,
+                );
+                if proposal.action == ProposalAction::PauseSale {
+                    primary_market::cpi::pause(cpi_ctx)?;
+                } else {
+                    primary_market::cpi::resume(cpi_ctx)?;
+                }
+            }
+            ProposalAction::SetPoolApy { apy } => {
+                require!(accounts.len() >= 2, GovernanceError::MissingAccounts);
+                require_pool_in_scope(governance, &accounts[0], &accounts[1])?;
+                let cpi_accounts = returns_distribution::cpi::accounts::SetApy {
+                    authority: treasury,
+                    pool: accounts[0].clone(),
+                    guardian_config: guardian_info,
+                };
+                let cpi_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.returns_distribution_program.to_account_info(),
+                    cpi_accounts,
+                    treasury_signer,
+                );
+                returns_distribution::cpi::set_apy(cpi_ctx, apy)?;
+            }
+            ProposalAction::TransitionStatus { new_status } => {
+                require!(!accounts.is_empty(), GovernanceError::MissingAccounts);
+                require_trailer_in_scope(governance, &accounts[0])?;
+                let cpi_accounts = asset_nft::cpi::accounts::TransitionStatus {
+                    authority: treasury,
+                    trailer_asset: accounts[0].clone(),
+                    guardian_config: guardian_info,
+                };
+                let cpi_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.asset_nft_program.to_account_info(),
+                    cpi_accounts,
+                    treasury_signer,
+                );
+                asset_nft::cpi::transition_status(cpi_ctx, new_status)?;
+            }
+            ProposalAction::Spend { recipient, mint, amount, memo } => {
+                match mint {
+                    None => {
+                        require!(!accounts.is_empty(), GovernanceError::MissingAccounts);
+                        require_keys_eq!(*accounts[0].key, recipient, GovernanceError::InvalidSpend);
+                        let cpi_ctx = CpiContext::new_with_signer(
+                            ctx.accounts.system_program.to_account_info(),
+                            system_program::Transfer {
+                                from: ctx.accounts.treasury.to_account_info(),
+                                to: accounts[0].clone(),
+                            },
+                            treasury_signer,
+                        );
+                        system_program::transfer(cpi_ctx, amount)?;
+                    }
+                    Some(mint) => {
+                        require!(accounts.len() >= 3, GovernanceError::MissingAccounts);
+                        require_keys_eq!(*accounts[2].key, token::ID, GovernanceError::InvalidSpend);
+                        let from = TokenAccount::try_deserialize(&mut &accounts[0].try_borrow_data()?[..])?;
+                        let to = TokenAccount::try_deserialize(&mut &accounts[1].try_borrow_data()?[..])?;
+                        require!(
+                            from.mint == mint && from.owner == ctx.accounts.treasury.key(),
+                            GovernanceError::InvalidSpend
+                        );
+                        require!(to.mint == mint && to.owner == recipient, GovernanceError::InvalidSpend);
+
+                        let cpi_ctx = CpiContext::new_with_signer(
+                            accounts[2].clone(),
+                            Transfer {
+                                from: accounts[0].clone(),
+                                to: accounts[1].clone(),
+                                authority: ctx.accounts.treasury.to_account_info(),
+                            },
+                            treasury_signer,
+                        );
+                        token::transfer(cpi_ctx, amount)?;
+                    }
+                }
+
+                emit!(TreasuryPayment {
+                    governance: governance_key,
+                    proposal: proposal.key(),
+                    recipient,
+                    mint,
+                    amount,
+                    memo,
+                });
+            }
+            ProposalAction::ExpandFleet { params } => {
+                expand_fleet(&ctx, &params, treasury_signer)?;
+            }
+        }
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.status = ProposalStatus::Executed;
+        proposal.executed_at = now;
+
+        emit!(ProposalExecuted {
+            proposal: proposal.key(),
+            action: proposal.action.clone(),
+            executor: ctx.accounts.executor.key(),
+        });
+        Ok(())
+    }
+}
+
+/// Creates the trailer in the governed series, opens its primary sale and
+/// deposits the full token supply in the sale vault. The treasury acts as
+/// trailer authority, sale authority, mint authority and rent payer; sale
+/// proceeds go to a treasury-owned payment account.
+fn expand_fleet<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, ExecuteProposal<'info>>,
+    params: &FleetExpansion,
+    treasury_signer: &[&[&[u8]]],
+) -> Result<()> {
+    let accounts = ctx.remaining_accounts;
+    require!(accounts.len() >= 10, GovernanceError::MissingAccounts);
+    let (trailer_asset, series_account, mint, sale) = (&accounts[0], &accounts[1], &accounts[2], &accounts[3]);
+    let (payment_mint, seller_token_account, payment_vault, token_vault) =
+        (&accounts[4], &accounts[5], &accounts[6], &accounts[7]);
+    let (token_program, rent) = (&accounts[8], &accounts[9]);
+
+    require_keys_eq!(*series_account.key, ctx.accounts.governance.target, GovernanceError::OutOfScope);
+    require_keys_eq!(*mint.key, params.mint, GovernanceError::InvalidExpansion);
+    require_keys_eq!(*payment_mint.key, params.payment_mint, GovernanceError::InvalidExpansion);
+    let proceeds = TokenAccount::try_deserialize(&mut &seller_token_account.try_borrow_data()?[..])?;
+    require_keys_eq!(proceeds.owner, ctx.accounts.treasury.key(), GovernanceError::InvalidExpansion);
+    let series_name = Series::try_deserialize(&mut &series_account.try_borrow_data()?[..])?.name;
+
+    let treasury = ctx.accounts.treasury.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let guardian_config = ctx.accounts.guardian_config.to_account_info();
+
+    let cpi_accounts = asset_nft::cpi::accounts::CreateTrailerNFT {
+        authority: treasury.clone(),
+        series_account: series_account.clone(),
+        trailer_asset: trailer_asset.clone(),
+        mint: mint.clone(),
+        token_program: token_program.clone(),
+        system_program: system_program.clone(),
+        rent: rent.clone(),
+        guardian_config: guardian_config.clone(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.asset_nft_program.to_account_info(),
+        cpi_accounts,
+        treasury_signer,
+    );
+    asset_nft::cpi::create_trailer_nft(
+        cpi_ctx,
+        params.name.clone(),
+        params.symbol.clone(),
+        params.uri.clone(),
+        series_name,
+        params.total_value,
+        params.token_price,
+        params.total_tokens,
+        params.apy,
+        params.term_years,
+    )?;
+
+    let cpi_accounts = primary_market::cpi::accounts::InitSale {
+        authority: treasury.clone(),
+        sale: sale.clone(),
+        mint: mint.clone(),
+        trailer_asset: trailer_asset.clone(),
+        payment_mint: payment_mint.clone(),
+        seller_token_account: seller_token_account.clone(),
+        payment_vault: payment_vault.clone(),
+        token_vault: token_vault.clone(),
+        token_program: token_program.clone(),
+        system_program,
+        guardian_config: guardian_config.clone(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.primary_market_program.to_account_info(),
+        cpi_accounts,
+        treasury_signer,
+    );
+    primary_market::cpi::init_sale(
+        cpi_ctx,
+        params.token_price,
+        params.total_tokens,
+        params.max_per_wallet,
+        params.max_wallet_bps,
+        params.cooling_off_period,
+    )?;
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.clone(),
+        MintTo {
+            mint: mint.clone(),
+            to: token_vault.clone(),
+            authority: treasury.clone(),
+        },
+        treasury_signer,
+    );
+    token::mint_to(cpi_ctx, params.total_tokens as u64)?;
+
+    let cpi_accounts = asset_nft::cpi::accounts::SetMarketAuthority {
+        authority: treasury.clone(),
+        trailer_asset: trailer_asset.clone(),
+        guardian_config: guardian_config.clone(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.asset_nft_program.to_account_info(),
+        cpi_accounts,
+        treasury_signer,
+    );
+    asset_nft::cpi::set_market_authority(cpi_ctx, *sale.key)?;
+
+    // The treasury is the authority, so it can't also approve liquidations
+    let cpi_accounts = asset_nft::cpi::accounts::SetLiquidationApprover {
+        liquidation_approver: treasury,
+        trailer_asset: trailer_asset.clone(),
+        guardian_config,
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.asset_nft_program.to_account_info(),
+        cpi_accounts,
+        treasury_signer,
+    );
+    asset_nft::cpi::set_liquidation_approver(cpi_ctx, ctx.accounts.governance.key())?;
+
+    emit!(FleetExpanded {
+        proposal: ctx.accounts.proposal.key(),
+        trailer_asset: *trailer_asset.key,
+        sale: *sale.key,
+        total_tokens: params.total_tokens,
+    });
+    Ok(())
+}
+
+/// Treasury PDA of the governance over `target`
+pub fn treasury_address(target: &Pubkey) -> Pubkey {
+    let (governance, _) = Pubkey::find_program_address(&[b"governance", target.as_ref()], &ID);
+    Pubkey::find_program_address(&[b"treasury", governance.as_ref()], &ID).0
+}
+
+/// Treasury of the series governance, which collects marketplace fees for
+/// every trailer in the series
+pub fn series_treasury_address(series_name: &str) -> Pubkey {
+    let (series, _) = Pubkey::find_program_address(&[b"series", series_name.as_bytes()], &asset_nft::ID);
+    treasury_address(&series)
+}
+
+fn require_trailer_in_scope(governance: &Governance, trailer_info: &AccountInfo) -> Result<TrailerAsset> {
+    require_keys_eq!(*trailer_info.owner, asset_nft::ID, GovernanceError::OutOfScope);
+    let trailer_asset = TrailerAsset::try_deserialize(&mut &trailer_info.try_borrow_data()?[..])?;
+    require!(governance.includes(trailer_info.key, &trailer_asset), GovernanceError::OutOfScope);
+    Ok(trailer_asset)
+}
+
+/// The sale must be the primary sale of a trailer within the governance scope
+fn require_sale_in_scope(governance: &Governance, sale_info: &AccountInfo, trailer_info: &AccountInfo) -> Result<()> {
+    require_keys_eq!(*sale_info.owner, primary_market::ID, GovernanceError::OutOfScope);
+    let sale = Sale::try_deserialize(&mut &sale_info.try_borrow_data()?[..])?;
+    let (trailer_key, _) = Pubkey::find_program_address(&[b"trailer", sale.mint.as_ref()], &asset_nft::ID);
+    require_keys_eq!(*trailer_info.key, trailer_key, GovernanceError::OutOfScope);
+    require_trailer_in_scope(governance, trailer_info)?;
+    Ok(())
+}
+
+/// The pool must distribute the returns of a trailer within the governance scope
+fn require_pool_in_scope(governance: &Governance, pool_info: &AccountInfo, trailer_info: &AccountInfo) -> Result<()> {
+    require_keys_eq!(*pool_info.owner, returns_distribution::ID, GovernanceError::OutOfScope);
+    let pool = Pool::try_deserialize(&mut &pool_info.try_borrow_data()?[..])?;
+    require_keys_eq!(pool.trailer_asset, *trailer_info.key, GovernanceError::OutOfScope);
+    require_trailer_in_scope(governance, trailer_info)?;
+    Ok(())
+}
+
+/// Sums the voter's balances over `(trailer_asset, token_account)` pairs,
+/// counting each trailer once and only if it falls within the governance scope
+fn voter_weight(governance: &Governance, voter: &Pubkey, accounts: &[AccountInfo]) -> Result<u64> {
+    require!(accounts.len().is_multiple_of(2), GovernanceError::InvalidVoterAccounts);
+
+    let mut seen: Vec<Pubkey> = Vec::with_capacity(accounts.len() / 2);
+    let mut weight: u64 = 0;
+    for pair in accounts.chunks(2) {
+        let (trailer_info, token_info) = (&pair[0], &pair[1]);
+        require_keys_eq!(*trailer_info.owner, asset_nft::ID, GovernanceError::InvalidVoterAccounts);
+        require_keys_eq!(*token_info.owner, token::ID, GovernanceError::InvalidVoterAccounts);
+        require!(!seen.contains(trailer_info.key), GovernanceError::DuplicateTrailer);
+        seen.push(*trailer_info.key);
+
+        let trailer_asset = TrailerAsset::try_deserialize(&mut &trailer_info.try_borrow_data()?[..])?;
+        require!(
+            governance.includes(trailer_info.key, &trailer_asset),
+            GovernanceError::OutOfScope
+        );
+
+        let token_account = TokenAccount::try_deserialize(&mut &token_info.try_borrow_data()?[..])?;
+        require_keys_eq!(token_account.mint, trailer_asset.mint, GovernanceError::InvalidVoterAccounts);
+        require_keys_eq!(token_account.owner, *voter, GovernanceError::InvalidVoterAccounts);
+
+        weight = weight.checked_add(token_account.amount).ok_or(GovernanceError::Overflow)?;
+    }
+    Ok(weight)
+}
+
+#[derive(Accounts)]
+pub struct InitGovernance<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: TrailerAsset or Series account, deserialized according to `scope`
+    #[account(owner = asset_nft::ID)]
+    pub target: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Governance::INIT_SPACE,
+        seeds = [b"governance", target.key().as_ref()],
+        bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"governance", governance.target.as_ref()],
+        bump = governance.bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + Proposal::INIT_SPACE,
+        seeds = [b"proposal", governance.key().as_ref(), &governance.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PostSnapshot<'info> {
+    pub snapshot_authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"governance", governance.target.as_ref()],
+        bump = governance.bump,
+        constraint = governance.config.snapshot_authority == snapshot_authority.key() @ GovernanceError::Unauthorized
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", governance.key().as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = governance
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireProposal<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(
+        seeds = [b"governance", governance.target.as_ref()],
+        bump = governance.bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", governance.key().as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = governance
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    #[account(
+        seeds = [b"governance", governance.target.as_ref()],
+        bump = governance.bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", governance.key().as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = governance
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        init_if_needed,
+        payer = voter,
+        space = 8 + VoteRecord::INIT_SPACE,
+        seeds = [b"vote", proposal.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Delegate<'info> {
+    #[account(mut)]
+    pub delegator: Signer<'info>,
+
+    #[account(
+        seeds = [b"governance", governance.target.as_ref()],
+        bump = governance.bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        init,
+        payer = delegator,
+        space = 8 + Delegation::INIT_SPACE,
+        seeds = [b"delegation", governance.key().as_ref(), delegator.key().as_ref()],
+        bump
+    )]
+    pub delegation: Account<'info, Delegation>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct Initialize {}
+pub struct RevokeDelegation<'info> {
+    #[account(mut)]
+    pub delegator: Signer<'info>,
+
+    #[account(
+        mut,
+        close = delegator,
+        seeds = [b"delegation", delegation.governance.as_ref(), delegator.key().as_ref()],
+        bump = delegation.bump,
+        has_one = delegator
+    )]
+    pub delegation: Account<'info, Delegation>,
+}
+
+#[derive(Accounts)]
+pub struct CastDelegatedVote<'info> {
+    #[account(mut)]
+    pub delegate: Signer<'info>,
+
+    #[account(
+        seeds = [b"governance", governance.target.as_ref()],
+        bump = governance.bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", governance.key().as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = governance
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        seeds = [b"delegation", governance.key().as_ref(), delegation.delegator.as_ref()],
+        bump = delegation.bump,
+        has_one = governance,
+        has_one = delegate
+    )]
+    pub delegation: Account<'info, Delegation>,
+
+    /// Fails if the delegator already voted, directly or through the delegate
+    #[account(
+        init,
+        payer = delegate,
+        space = 8 + VoteRecord::INIT_SPACE,
+        seeds = [b"vote", proposal.key().as_ref(), delegation.delegator.as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeProposal<'info> {
+    #[account(
+        seeds = [b"governance", governance.target.as_ref()],
+        bump = governance.bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", governance.key().as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = governance
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"governance", governance.target.as_ref()],
+        bump = governance.bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", governance.key().as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = governance
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// Data-less PDA holding the governance funds; receives a share of
+    /// marketplace fees
+    #[account(mut, seeds = [b"treasury", governance.key().as_ref()], bump)]
+    pub treasury: SystemAccount<'info>,
+
+    pub asset_nft_program: Program<'info, AssetNft>,
+    pub primary_market_program: Program<'info, PrimaryMarket>,
+    pub returns_distribution_program: Program<'info, ReturnsDistribution>,
+    pub system_program: Program<'info, System>,
+
+    /// Forwarded to every CPI so guardian pauses on the target programs apply
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+/// Voting power a wallet lends to another under one governance
+#[account]
+#[derive(InitSpace)]
+pub struct Delegation {
+    pub governance: Pubkey,
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+/// Governance over a single trailer or a whole series
+#[account]
+#[derive(InitSpace)]
+pub struct Governance {
+    pub scope: GovernanceScope,
+    /// TrailerAsset or Series account governed
+    pub target: Pubkey,
+    pub voting_period: i64,
+    pub config: GovernanceConfig,
+    pub proposal_count: u64,
+    pub bump: u8,
+}
+
+impl Governance {
+    pub fn includes(&self, trailer_key: &Pubkey, trailer_asset: &TrailerAsset) -> bool {
+        match self.scope {
+            GovernanceScope::Trailer => *trailer_key == self.target,
+            GovernanceScope::Series => {
+                let (series, _) = Pubkey::find_program_address(
+                    &[b"series", trailer_asset.series.as_bytes()],
+                    &asset_nft::ID,
+                );
+                series == self.target
+            }
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum GovernanceScope {
+    Trailer,
+    Series,
+}
+
+/// Rules a proposal must meet before it can be executed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct GovernanceConfig {
+    /// Minimum share of the total weight that must vote, abstentions included
+    pub quorum_bps: u16,
+    /// Minimum share of for votes among for + against
+    pub threshold_bps: u16,
+    /// Delay between a proposal passing and its execution
+    pub timelock: i64,
+    /// Off-chain indexer that posts balance snapshots for new proposals
+    pub snapshot_authority: Pubkey,
+    /// Time after creation within which the snapshot must be posted
+    pub snapshot_window: i64,
+}
+
+impl GovernanceConfig {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.quorum_bps as u64 <= BPS_DENOMINATOR
+                && self.threshold_bps > 0
+                && self.threshold_bps as u64 <= BPS_DENOMINATOR
+                && (0..=MAX_TIMELOCK).contains(&self.timelock)
+                && self.snapshot_authority != Pubkey::default()
+                && (1..=MAX_SNAPSHOT_WINDOW).contains(&self.snapshot_window),
+            GovernanceError::InvalidConfig
+        );
+        Ok(())
+    }
+}
+
+/// Instruction a proposal executes once passed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub enum ProposalAction {
+    /// Non-binding vote
+    Signal,
+    UpdateConfig { config: GovernanceConfig },
+    /// Requires the treasury PDA to hold the trailer's lock manager role
+    SetLockStatus { is_locked: bool },
+    /// Requires the treasury PDA to be the trailer's liquidation approver, or
+    /// the governance PDA when the treasury owns the trailer
+    ApproveLiquidation {
+        reason: LiquidationReason,
+        reason_hash: [u8; 32],
+        proceeds: u64,
+        sweep_deadline: i64,
+    },
+    /// Requires the treasury PDA to be the sale authority
+    PauseSale,
+    ResumeSale,
+    /// Requires the treasury PDA to be the pool authority
+    SetPoolApy { apy: u16 },
+    /// Adds a trailer to the governed series and opens its sale; requires the
+    /// series authority to have been handed to the treasury with
+    /// `asset_nft::set_series_authority`. The treasury owns the new trailer
+    /// and the governance PDA approves its liquidation.
+    ExpandFleet { params: FleetExpansion },
+    /// Pays out of the treasury; `mint` is `None` for SOL
+    Spend {
+        recipient: Pubkey,
+        mint: Option<Pubkey>,
+        amount: u64,
+        #[max_len(MAX_MEMO_LEN)]
+        memo: String,
+    },
+    /// Moves a trailer through its lifecycle; requires the treasury PDA to be
+    /// the trailer authority. Liquidation goes through `ApproveLiquidation`.
+    TransitionStatus { new_status: AssetStatus },
+}
+
+/// Parameters of a new trailer and its primary sale. The mint is created
+/// beforehand with the treasury as mint authority and the asset_nft freeze
+/// PDA as freeze authority.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub struct FleetExpansion {
+    pub mint: Pubkey,
+    #[max_len(50)]
+    pub name: String,
+    #[max_len(10)]
+    pub symbol: String,
+    #[max_len(200)]
+    pub uri: String,
+    pub total_value: u64,
+    pub token_price: u64,
+    pub total_tokens: u16,
+    pub apy: u16,
+    pub term_years: u8,
+    pub payment_mint: Pubkey,
+    pub max_per_wallet: u16,
+    pub max_wallet_bps: u16,
+    pub cooling_off_period: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum ProposalStatus {
+    /// Waiting for the balance snapshot
+    Pending,
+    Voting,
+    Succeeded,
+    Defeated,
+    Executed,
+    /// No snapshot arrived in time, or the proposer withdrew it
+    Expired,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    pub governance: Pubkey,
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    #[max_len(64)]
+    pub title: String,
+    /// Hash of the off-chain proposal description
+    pub description_hash: [u8; 32],
+    pub action: ProposalAction,
+    pub status: ProposalStatus,
+    /// Slot at which voting balances are checkpointed
+    pub snapshot_slot: u64,
+    /// Merkle root of `(voter, weight)` leaves at `snapshot_slot`
+    pub snapshot_root: [u8; 32],
+    /// Sum of all snapshot weights, the base for quorum
+    pub total_weight: u64,
+    pub created_at: i64,
+    pub voting_starts_at: i64,
+    pub voting_ends_at: i64,
+    pub for_votes: u64,
+    pub against_votes: u64,
+    pub abstain_votes: u64,
+    pub executable_at: i64,
+    pub executed_at: i64,
+    pub bump: u8,
+}
+
+impl Proposal {
+    pub fn is_voting(&self, now: i64) -> bool {
+        now >= self.voting_starts_at && now < self.voting_ends_at
+    }
+
+    pub fn snapshot_expired(&self, config: &GovernanceConfig, now: i64) -> bool {
+        now > self.created_at.saturating_add(config.snapshot_window)
+    }
+
+    pub fn tally(&mut self, choice: VoteChoice, weight: u64) -> Result<()> {
+        let counter = match choice {
+            VoteChoice::For => &mut self.for_votes,
+            VoteChoice::Against => &mut self.against_votes,
+            VoteChoice::Abstain => &mut self.abstain_votes,
+        };
+        *counter = counter.checked_add(weight).ok_or(GovernanceError::Overflow)?;
+        Ok(())
+    }
+
+    /// Checks a `(voter, weight)` leaf against the snapshot root using
+    /// sorted-pair sha256 hashing
+    pub fn verify_weight(&self, voter: &Pubkey, weight: u64, proof: &[[u8; 32]]) -> bool {
+        let mut node = hashv(&[voter.as_ref(), &weight.to_le_bytes()]).to_bytes();
+        for sibling in proof {
+            node = if node <= *sibling {
+                hashv(&[&node, sibling]).to_bytes()
+            } else {
+                hashv(&[sibling, &node]).to_bytes()
+            };
+        }
+        node == self.snapshot_root
+    }
+
+    pub fn untally(&mut self, choice: VoteChoice, weight: u64) -> Result<()> {
+        let counter = match choice {
+            VoteChoice::For => &mut self.for_votes,
+            VoteChoice::Against => &mut self.against_votes,
+            VoteChoice::Abstain => &mut self.abstain_votes,
+        };
+        *counter = counter.checked_sub(weight).ok_or(GovernanceError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn passed(&self, config: &GovernanceConfig) -> bool {
+        let participation = self.for_votes as u128 + self.against_votes as u128 + self.abstain_votes as u128;
+        let decisive = self.for_votes as u128 + self.against_votes as u128;
+        let quorum_met = participation * BPS_DENOMINATOR as u128
+            >= self.total_weight as u128 * config.quorum_bps as u128;
+        let threshold_met = self.for_votes > 0
+            && self.for_votes as u128 * BPS_DENOMINATOR as u128 >= decisive * config.threshold_bps as u128;
+        quorum_met && threshold_met
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum VoteChoice {
+    For,
+    Against,
+    Abstain,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct VoteRecord {
+    pub proposal: Pubkey,
+    /// Wallet whose tokens the vote counts
+    pub voter: Pubkey,
+    /// The voter itself or its delegate
+    pub cast_by: Pubkey,
+    pub choice: VoteChoice,
+    pub weight: u64,
+    pub voted_at: i64,
+    pub bump: u8,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub governance: Pubkey,
+    pub proposal: Pubkey,
+    pub proposer: Pubkey,
+    pub snapshot_slot: u64,
+}
+
+#[event]
+pub struct VoteCast {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub cast_by: Pubkey,
+    pub choice: VoteChoice,
+    pub weight: u64,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub proposal: Pubkey,
+    pub action: ProposalAction,
+    pub executor: Pubkey,
+}
+
+#[event]
+pub struct TreasuryPayment {
+    pub governance: Pubkey,
+    pub proposal: Pubkey,
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub memo: String,
+}
+
+#[event]
+pub struct FleetExpanded {
+    pub proposal: Pubkey,
+    pub trailer_asset: Pubkey,
+    pub sale: Pubkey,
+    pub total_tokens: u16,
+}
+
+#[error_code]
+pub enum GovernanceError {
+    #[msg("Voting period out of range")]
+    InvalidVotingPeriod,
+    #[msg("Signer is not the asset authority")]
+    Unauthorized,
+    #[msg("Title is empty or too long")]
+    TitleTooLong,
+    #[msg("Voter holds no tokens in scope")]
+    NoVotingPower,
+    #[msg("Proposal is not open for voting")]
+    VotingClosed,
+    #[msg("Voter accounts must be (trailer_asset, token_account) pairs owned by the voter")]
+    InvalidVoterAccounts,
+    #[msg("Trailer counted twice")]
+    DuplicateTrailer,
+    #[msg("Trailer is outside the governance scope")]
+    OutOfScope,
+    #[msg("Arithmetic overflow")]
+    Overflow,
+    #[msg("Quorum, threshold or timelock out of range")]
+    InvalidConfig,
+    #[msg("Voting window has not ended")]
+    VotingOpen,
+    #[msg("Proposal has not succeeded or was already executed")]
+    NotExecutable,
+    #[msg("Timelock has not expired")]
+    TimelockActive,
+    #[msg("Accounts required by the proposal action are missing")]
+    MissingAccounts,
+    #[msg("Wallet already voted on this proposal")]
+    AlreadyVoted,
+    #[msg("Cannot delegate to yourself")]
+    InvalidDelegate,
+    #[msg("Snapshot total weight must be positive")]
+    InvalidSnapshot,
+    #[msg("Snapshot already posted for this proposal")]
+    SnapshotPosted,
+    #[msg("Weight does not match the snapshot")]
+    InvalidProof,
+    #[msg("Spend amount, memo or accounts are invalid")]
+    InvalidSpend,
+    #[msg("Accounts do not match the fleet expansion parameters")]
+    InvalidExpansion,
+    #[msg("The snapshot window for this proposal has closed")]
+    SnapshotWindowClosed,
+    #[msg("The snapshot window is still open")]
+    SnapshotWindowOpen,
+    #[msg("Liquidation goes through ApproveLiquidation")]
+    InvalidTransition,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proposal(for_votes: u64, against_votes: u64, abstain_votes: u64, total_weight: u64) -> Proposal {
+        Proposal {
+            governance: Pubkey::default(),
+            proposal_id: 0,
+            proposer: Pubkey::default(),
+            title: String::new(),
+            description_hash: [0; 32],
+            action: ProposalAction::Signal,
+            status: ProposalStatus::Voting,
+            snapshot_slot: 0,
+            snapshot_root: [0; 32],
+            total_weight,
+            created_at: 0,
+            voting_starts_at: 0,
+            voting_ends_at: 0,
+            for_votes,
+            against_votes,
+            abstain_votes,
+            executable_at: 0,
+            executed_at: 0,
+            bump: 0,
+        }
+    }
+
+    const CONFIG: GovernanceConfig = GovernanceConfig {
+        quorum_bps: 2_000,
+        threshold_bps: 5_001,
+        timelock: 0,
+        snapshot_authority: Pubkey::new_from_array([1; 32]),
+        snapshot_window: 3_600,
+    };
+
+    #[test]
+    fn passes_with_quorum_and_majority() {
+        assert!(proposal(150, 50, 0, 1_000).passed(&CONFIG));
+        // Abstentions count towards quorum but not the threshold
+        assert!(proposal(60, 40, 100, 1_000).passed(&CONFIG));
+    }
+
+    #[test]
+    fn fails_without_quorum() {
+        assert!(!proposal(199, 0, 0, 1_000).passed(&CONFIG));
+        assert!(proposal(200, 0, 0, 1_000).passed(&CONFIG));
+    }
+
+    #[test]
+    fn fails_on_a_tie_or_without_for_votes() {
+        assert!(!proposal(100, 100, 0, 1_000).passed(&CONFIG));
+        assert!(!proposal(0, 0, 500, 1_000).passed(&CONFIG));
+        let no_quorum = GovernanceConfig { quorum_bps: 0, ..CONFIG };
+        assert!(!proposal(0, 0, 0, 0).passed(&no_quorum));
+    }
+
+    fn leaf(voter: &Pubkey, weight: u64) -> [u8; 32] {
+        hashv(&[voter.as_ref(), &weight.to_le_bytes()]).to_bytes()
+    }
+
+    fn parent(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+        if a <= b {
+            hashv(&[a, b]).to_bytes()
+        } else {
+            hashv(&[b, a]).to_bytes()
+        }
+    }
+
+    #[test]
+    fn verifies_weights_against_the_snapshot_root() {
+        let voters = [
+            Pubkey::new_from_array([1; 32]),
+            Pubkey::new_from_array([2; 32]),
+            Pubkey::new_from_array([3; 32]),
+        ];
+        let leaves = [leaf(&voters[0], 100), leaf(&voters[1], 250), leaf(&voters[2], 50)];
+        let left = parent(&leaves[0], &leaves[1]);
+        let mut snapshot = proposal(0, 0, 0, 400);
+        snapshot.snapshot_root = parent(&left, &leaves[2]);
+
+        assert!(snapshot.verify_weight(&voters[0], 100, &[leaves[1], leaves[2]]));
+        assert!(snapshot.verify_weight(&voters[1], 250, &[leaves[0], leaves[2]]));
+        assert!(snapshot.verify_weight(&voters[2], 50, &[left]));
+
+        // Inflated weight, foreign voter or truncated proof
+        assert!(!snapshot.verify_weight(&voters[0], 101, &[leaves[1], leaves[2]]));
+        assert!(!snapshot.verify_weight(&Pubkey::new_from_array([9; 32]), 100, &[leaves[1], leaves[2]]));
+        assert!(!snapshot.verify_weight(&voters[0], 100, &[leaves[1]]));
+    }
+
+    #[test]
+    fn snapshot_window_closes_after_creation_plus_window() {
+        let mut pending = proposal(0, 0, 0, 0);
+        pending.created_at = 1_000;
+        assert!(!pending.snapshot_expired(&CONFIG, 1_000 + CONFIG.snapshot_window));
+        assert!(pending.snapshot_expired(&CONFIG, 1_001 + CONFIG.snapshot_window));
+    }
+
+    #[test]
+    fn rejects_configs_without_a_snapshot_authority_or_window() {
+        assert!(CONFIG.validate().is_ok());
+        let no_authority = GovernanceConfig { snapshot_authority: Pubkey::default(), ..CONFIG };
+        assert!(no_authority.validate().is_err());
+        let no_window = GovernanceConfig { snapshot_window: 0, ..CONFIG };
+        assert!(no_window.validate().is_err());
+        let long_window = GovernanceConfig { snapshot_window: MAX_SNAPSHOT_WINDOW + 1, ..CONFIG };
+        assert!(long_window.validate().is_err());
+    }
+}
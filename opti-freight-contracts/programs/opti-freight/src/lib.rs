@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
+use asset_nft::GuardianConfig;
 
 declare_id!("7x4rNdNN9Szce8qfasDGiV3srApWcJ339t8iGAyKjrga");
 
@@ -10,24 +11,31 @@ const PENALTY: u64 = 25_000_000; // 0.025 SOL en lamports - PARA PRUEBAS
 const SECONDARY_FEE_BPS: u16 = 300; // 3%
 const MIN_RESALE: u64 = 145_000_000; // 0.145 SOL mínimo para reventa - PARA PRUEBAS
 const DISTRIBUTION_DAY: u8 = 20;
+const SALE_SPACE: usize = 8 + 32 + 2 + 2 + 1 + 1 + 1;
+// Layout desplegado antes de agregar `paused`
+const LEGACY_SALE_SPACE: usize = SALE_SPACE - 1;
 
 #[program]
 pub mod opti_freight {
     use super::*;
 
     pub fn init_sale(ctx: Context<InitSale>) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::InitSale::DISCRIMINATOR)?;
         let sale = &mut ctx.accounts.sale;
         sale.authority = ctx.accounts.authority.key();
         sale.total = TOKENS_PER_TRAILER;
         sale.sold = 0;
         sale.active = true;
+        sale.paused = false;
         sale.bump = ctx.bumps.sale;
         Ok(())
     }
 
     pub fn buy_primary(ctx: Context<BuyPrimary>, amount: u16) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::BuyPrimary::DISCRIMINATOR)?;
         let sale = &mut ctx.accounts.sale;
         require!(sale.active, ErrorCode::NotActive);
+        require!(!sale.paused, ErrorCode::Paused);
         require!(sale.sold + amount <= sale.total, ErrorCode::SoldOut);
 
         let base_cost = TOKEN_PRICE * amount as u64;
@@ -65,11 +73,74 @@ pub mod opti_freight {
         Ok(())
     }
 
-    pub fn close_sale(_ctx: Context<CloseSale>) -> Result<()> {
+    pub fn pause_sale(ctx: Context<SetSalePause>) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::PauseSale::DISCRIMINATOR)?;
+        ctx.accounts.sale.paused = true;
+        Ok(())
+    }
+
+    pub fn resume_sale(ctx: Context<SetSalePause>) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::ResumeSale::DISCRIMINATOR)?;
+        ctx.accounts.sale.paused = false;
+        Ok(())
+    }
+
+    /// Lleva una Sale creada antes de `paused` al layout actual: agranda la cuenta
+    /// un byte, inserta `paused = false` antes del bump y cubre la renta extra
+    pub fn migrate_sale(ctx: Context<MigrateSale>) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::MigrateSale::DISCRIMINATOR)?;
+        let sale = ctx.accounts.sale.to_account_info();
+        {
+            let data = sale.try_borrow_data()?;
+            require!(data.len() == LEGACY_SALE_SPACE, ErrorCode::AlreadyMigrated);
+            require!(data[..8] == *Sale::DISCRIMINATOR, ErrorCode::InvalidSale);
+            require!(data[8..40] == ctx.accounts.authority.key().to_bytes(), ErrorCode::InvalidSale);
+        }
+
+        let rent = Rent::get()?.minimum_balance(SALE_SPACE);
+        let missing = rent.saturating_sub(sale.lamports());
+        if missing > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: sale.clone(),
+                    },
+                ),
+                missing,
+            )?;
+        }
+
+        sale.resize(SALE_SPACE)?;
+        // [disc 8][authority 32][total 2][sold 2][active 1][bump 1] -> ...[active][paused][bump]
+        let mut data = sale.try_borrow_mut_data()?;
+        data[SALE_SPACE - 1] = data[SALE_SPACE - 2];
+        data[SALE_SPACE - 2] = 0;
+        Ok(())
+    }
+
+    // Los pagos van directo al vendedor, así que no hay escrow ni inventario que
+    // devolver: la venta debe estar agotada o pausada antes de cerrarla
+    pub fn close_sale(ctx: Context<CloseSale>) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::CloseSale::DISCRIMINATOR)?;
+        let sale = &ctx.accounts.sale;
+        require!(!sale.active || sale.paused, ErrorCode::StillActive);
+
+        emit!(SaleClosed {
+            sale: sale.key(),
+            authority: sale.authority,
+            total: sale.total,
+            sold: sale.sold,
+            raised: TOKEN_PRICE * sale.sold as u64,
+            closed_at: Clock::get()?.unix_timestamp,
+        });
+
         Ok(())
     }
 
     pub fn create_listing(ctx: Context<CreateListing>, price: u64, amount: u16) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::CreateListing::DISCRIMINATOR)?;
         require!(price >= MIN_RESALE, ErrorCode::PriceTooLow);
         
         let listing = &mut ctx.accounts.listing;
@@ -82,6 +153,7 @@ pub mod opti_freight {
     }
 
     pub fn buy_secondary(ctx: Context<BuySecondary>, amount: u16) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::BuySecondary::DISCRIMINATOR)?;
         let listing = &mut ctx.accounts.listing;
         require!(listing.active, ErrorCode::NotActive);
         require!(amount <= listing.amount, ErrorCode::SoldOut);
@@ -125,11 +197,24 @@ pub mod opti_freight {
         Ok(())
     }
 
-    pub fn cancel_listing(_ctx: Context<CancelListing>) -> Result<()> {
+    pub fn cancel_listing(ctx: Context<CancelListing>) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::CancelListing::DISCRIMINATOR)?;
+        let listing = &ctx.accounts.listing;
+        require!(listing.active, ErrorCode::NotActive);
+
+        emit!(ListingCancelled {
+            listing: listing.key(),
+            seller: listing.seller,
+            price: listing.price,
+            unsold: listing.amount,
+            cancelled_at: Clock::get()?.unix_timestamp,
+        });
+
         Ok(())
     }
 
     pub fn distribute_monthly(ctx: Context<DistributeMonthly>, user_tokens: u16) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::DistributeMonthly::DISCRIMINATOR)?;
         let clock = Clock::get()?;
         let day = ((clock.unix_timestamp / 86400) % 30) as u8;
 
@@ -150,9 +235,11 @@ pub mod opti_freight {
 pub struct InitSale<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
-    #[account(init, payer = authority, space = 8 + 32 + 2 + 2 + 1 + 1, seeds = [b"sale", authority.key().as_ref()], bump)]
+    #[account(init, payer = authority, space = SALE_SPACE, seeds = [b"sale", authority.key().as_ref()], bump)]
     pub sale: Account<'info, Sale>,
     pub system_program: Program<'info, System>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
 }
 
 #[derive(Accounts)]
@@ -168,6 +255,29 @@ pub struct BuyPrimary<'info> {
     #[account(mut)]
     pub platform: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetSalePause<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut, has_one = authority)]
+    pub sale: Account<'info, Sale>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateSale<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: Sale con el layout anterior, no deserializable como `Sale`; se valida en la instrucción
+    #[account(mut, owner = crate::ID, seeds = [b"sale", authority.key().as_ref()], bump)]
+    pub sale: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
 }
 
 #[derive(Accounts)]
@@ -176,6 +286,8 @@ pub struct CloseSale<'info> {
     pub authority: Signer<'info>,
     #[account(mut, has_one = authority, close = authority)]
     pub sale: Account<'info, Sale>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
 }
 
 #[derive(Accounts)]
@@ -185,6 +297,8 @@ pub struct CreateListing<'info> {
     #[account(init, payer = seller, space = 8 + 32 + 8 + 2 + 1 + 1, seeds = [b"listing", seller.key().as_ref()], bump)]
     pub listing: Account<'info, Listing>,
     pub system_program: Program<'info, System>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
 }
 
 #[derive(Accounts)]
@@ -200,6 +314,8 @@ pub struct BuySecondary<'info> {
     #[account(mut)]
     pub platform: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
 }
 
 #[derive(Accounts)]
@@ -208,6 +324,8 @@ pub struct CancelListing<'info> {
     pub seller: Signer<'info>,
     #[account(mut, has_one = seller, close = seller)]
     pub listing: Account<'info, Listing>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
 }
 
 #[derive(Accounts)]
@@ -218,6 +336,8 @@ pub struct DistributeMonthly<'info> {
     #[account(mut)]
     pub pool: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
 }
 
 #[account]
@@ -226,6 +346,7 @@ pub struct Sale {
     pub total: u16,
     pub sold: u16,
     pub active: bool,
+    pub paused: bool,
     pub bump: u8,
 }
 
@@ -238,6 +359,25 @@ pub struct Listing {
     pub bump: u8,
 }
 
+#[event]
+pub struct SaleClosed {
+    pub sale: Pubkey,
+    pub authority: Pubkey,
+    pub total: u16,
+    pub sold: u16,
+    pub raised: u64,
+    pub closed_at: i64,
+}
+
+#[event]
+pub struct ListingCancelled {
+    pub listing: Pubkey,
+    pub seller: Pubkey,
+    pub price: u64,
+    pub unsold: u16,
+    pub cancelled_at: i64,
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Not active")]
@@ -248,4 +388,12 @@ pub enum ErrorCode {
     PriceTooLow,
     #[msg("Wrong day, must be 20th")]
     WrongDay,
+    #[msg("Sale paused")]
+    Paused,
+    #[msg("Sale still active, pause it before closing")]
+    StillActive,
+    #[msg("Sale already uses the current layout")]
+    AlreadyMigrated,
+    #[msg("Not a sale owned by this authority")]
+    InvalidSale,
 }
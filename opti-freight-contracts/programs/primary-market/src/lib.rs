@@ -1,28 +1,80 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
+use asset_nft::program::AssetNft;
+use asset_nft::{AssetStatus, GuardianConfig, Series, TrailerAsset};
 
 declare_id!("Az1M72qgA5REQjiV789DrSqgMG1UGrL7puRXEqBCAHFQ");
 
+const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Mercado secundario: único programa autorizado a mover posiciones. No se
+/// importa el crate para no crear una dependencia circular.
+pub const SECONDARY_MARKET_ID: Pubkey = pubkey!("DakwaYqG3tV9Jjgy5GokyQJd3JWb74Qx66JHqbZicsZX");
+
 #[program]
 pub mod primary_market {
     use super::*;
 
-    pub fn init_sale(ctx: Context<InitSale>, price: u64, total: u16) -> Result<()> {
+    pub fn init_sale(
+        ctx: Context<InitSale>,
+        price: u64,
+        total: u16,
+        max_per_wallet: u16,
+        max_wallet_bps: u16,
+        cooling_off_period: i64,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::InitSale::DISCRIMINATOR)?;
+        require!(max_wallet_bps as u64 <= BPS_DENOMINATOR, Err::InvalidLimit);
+        require!(cooling_off_period >= 0, Err::InvalidCoolingOff);
+        // Un porcentaje que redondea a 0 tokens bloquearía toda compra
+        require!(
+            max_wallet_bps == 0 || (total as u64 * max_wallet_bps as u64) / BPS_DENOMINATOR > 0,
+            Err::InvalidLimit
+        );
+
         let sale = &mut ctx.accounts.sale;
         sale.authority = ctx.accounts.authority.key();
+        sale.mint = ctx.accounts.mint.key();
+        sale.payment_mint = ctx.accounts.payment_mint.key();
+        sale.seller_token_account = ctx.accounts.seller_token_account.key();
         sale.price = price;
         sale.total = total;
         sale.sold = 0;
+        sale.pending_tokens = 0;
+        sale.pending_purchases = 0;
+        sale.next_purchase_id = 0;
+        sale.max_per_wallet = max_per_wallet;
+        sale.max_wallet_bps = max_wallet_bps;
+        sale.cooling_off_period = cooling_off_period;
         sale.active = true;
+        sale.paused = false;
+        sale.closed = false;
         sale.bump = ctx.bumps.sale;
         Ok(())
     }
 
+    /// Compra tokens en período de desistimiento: los USDC quedan en el vault de
+    /// pagos y los tokens reservados en el vault de la venta hasta la finalización
     pub fn buy(ctx: Context<Buy>, amount: u16) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::Buy::DISCRIMINATOR)?;
         let sale = &mut ctx.accounts.sale;
         require!(sale.active, Err::NotActive);
+        require!(!sale.paused, Err::Paused);
+        require!(
+            ctx.accounts.trailer_asset.status == AssetStatus::Fundraising,
+            Err::NotFundraising
+        );
         require!(sale.sold + amount <= sale.total, Err::NotEnough);
 
+        // El vault debe cubrir todas las compras pendientes más esta
+        let reserved = sale.pending_tokens.checked_add(amount).ok_or(Err::Overflow)?;
+        require!(ctx.accounts.token_vault.amount >= reserved as u64, Err::NotEnough);
+
+        // Validar límite de concentración acumulado en la posición del comprador
+        let position = &mut ctx.accounts.position;
+        let new_holding = position.amount.checked_add(amount).ok_or(Err::Overflow)?;
+        sale.check_wallet_limit(new_holding)?;
+
         // Calcular costo total en USDC
         let total_cost = (sale.price as u128)
             .checked_mul(amount as u128)
@@ -31,71 +83,612 @@ pub mod primary_market {
         require!(total_cost <= u64::MAX as u128, Err::Overflow);
         let total_cost = total_cost as u64;
 
-        // Transferir USDC del comprador al vendedor
+        // Transferir USDC del comprador al escrow de la venta
         let cpi_accounts = Transfer {
             from: ctx.accounts.buyer_token_account.to_account_info(),
-            to: ctx.accounts.seller_token_account.to_account_info(),
+            to: ctx.accounts.payment_vault.to_account_info(),
             authority: ctx.accounts.buyer.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
         token::transfer(cpi_ctx, total_cost)?;
 
+        if position.owner == Pubkey::default() {
+            position.owner = ctx.accounts.buyer.key();
+            position.sale = sale.key();
+            position.bump = ctx.bumps.position;
+        }
+        position.amount = new_holding;
+
+        let now = Clock::get()?.unix_timestamp;
+        let purchase = &mut ctx.accounts.purchase;
+        purchase.sale = sale.key();
+        purchase.buyer = ctx.accounts.buyer.key();
+        purchase.id = sale.next_purchase_id;
+        purchase.amount = amount;
+        purchase.cost = total_cost;
+        purchase.purchased_at = now;
+        purchase.release_at = now
+            .checked_add(sale.cooling_off_period)
+            .ok_or(Err::Overflow)?;
+        purchase.bump = ctx.bumps.purchase;
+
+        sale.next_purchase_id += 1;
+        sale.pending_tokens = reserved;
+        sale.pending_purchases += 1;
         sale.sold += amount;
         if sale.sold >= sale.total {
             sale.active = false;
         }
 
-        msg!("Sold {} tokens for {} USDC", amount, total_cost);
+        sync_tokens_sold(
+            &ctx.accounts.sale,
+            &ctx.accounts.trailer_asset,
+            &ctx.accounts.series,
+            &ctx.accounts.asset_nft_program,
+            &ctx.accounts.guardian_config,
+        )?;
+
+        msg!(
+            "Reserved {} tokens for {} USDC - cancellable until {}",
+            amount,
+            total_cost,
+            purchase.release_at
+        );
+        Ok(())
+    }
+
+    /// Cancela una compra dentro del período de desistimiento con reembolso total
+    pub fn cancel_purchase(ctx: Context<CancelPurchase>) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::CancelPurchase::DISCRIMINATOR)?;
+        let purchase = &ctx.accounts.purchase;
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < purchase.release_at, Err::CoolingOffExpired);
+
+        // Reembolsar USDC desde el escrow usando el PDA de la venta como autoridad
+        let sale = &ctx.accounts.sale;
+        let mint_key = sale.mint;
+        let seeds = &[b"sale".as_ref(), mint_key.as_ref(), &[sale.bump]];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.payment_vault.to_account_info(),
+            to: ctx.accounts.buyer_token_account.to_account_info(),
+            authority: ctx.accounts.sale.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, purchase.cost)?;
+
+        let amount = purchase.amount;
+        let position = &mut ctx.accounts.position;
+        position.amount = position.amount.checked_sub(amount).ok_or(Err::Overflow)?;
+
+        // Liberar los tokens reservados para que vuelvan a estar a la venta
+        let sale = &mut ctx.accounts.sale;
+        sale.pending_tokens -= amount;
+        sale.pending_purchases -= 1;
+        sale.sold -= amount;
+        sale.active = true;
+
+        sync_tokens_sold(
+            &ctx.accounts.sale,
+            &ctx.accounts.trailer_asset,
+            &ctx.accounts.series,
+            &ctx.accounts.asset_nft_program,
+            &ctx.accounts.guardian_config,
+        )?;
+
+        msg!("Purchase cancelled - {} tokens released, {} USDC refunded", amount, purchase.cost);
+        Ok(())
+    }
+
+    /// Finaliza una compra vencido el período de desistimiento. Cualquiera puede
+    /// ejecutarla: libera los USDC al vendedor y los tokens al comprador
+    pub fn finalize_purchase(ctx: Context<FinalizePurchase>) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::FinalizePurchase::DISCRIMINATOR)?;
+        let purchase = &ctx.accounts.purchase;
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= purchase.release_at, Err::CoolingOffActive);
+
+        let sale = &ctx.accounts.sale;
+        let mint_key = sale.mint;
+        let seeds = &[b"sale".as_ref(), mint_key.as_ref(), &[sale.bump]];
+        let signer = &[&seeds[..]];
+
+        // PASO 1: USDC del escrow al vendedor
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.payment_vault.to_account_info(),
+            to: ctx.accounts.seller_token_account.to_account_info(),
+            authority: ctx.accounts.sale.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token::transfer(cpi_ctx, purchase.cost)?;
+
+        // PASO 2: Tokens del vault al comprador
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.token_vault.to_account_info(),
+            to: ctx.accounts.buyer_asset_account.to_account_info(),
+            authority: ctx.accounts.sale.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token::transfer(cpi_ctx, purchase.amount as u64)?;
+
+        let amount = purchase.amount;
+        let cost = purchase.cost;
+        let sale = &mut ctx.accounts.sale;
+        sale.pending_tokens -= amount;
+        sale.pending_purchases -= 1;
+
+        msg!("Purchase finalized - {} tokens delivered, {} USDC released", amount, cost);
+        Ok(())
+    }
+
+    /// Mueve unidades entre posiciones aplicando el mismo límite de concentración
+    /// que la venta primaria. Solo el mercado secundario puede invocarla vía CPI,
+    /// firmando con su PDA `market_authority`.
+    pub fn transfer_position(ctx: Context<TransferPosition>, amount: u16) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::TransferPosition::DISCRIMINATOR)?;
+        let sale = &ctx.accounts.sale;
+        let from_position = &mut ctx.accounts.from_position;
+        require!(from_position.amount >= amount, Err::NotEnough);
+
+        let to_position = &mut ctx.accounts.to_position;
+        let new_holding = to_position.amount.checked_add(amount).ok_or(Err::Overflow)?;
+        sale.check_wallet_limit(new_holding)?;
+
+        from_position.amount -= amount;
+
+        if to_position.owner == Pubkey::default() {
+            to_position.owner = ctx.accounts.recipient.key();
+            to_position.sale = sale.key();
+            to_position.bump = ctx.bumps.to_position;
+        }
+        to_position.amount = new_holding;
+
+        msg!("Position transfer: {} tokens to {}", amount, to_position.owner);
+        Ok(())
+    }
+
+    /// Suspende temporalmente las compras sin afectar las compras pendientes
+    pub fn pause(ctx: Context<SetPause>) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::Pause::DISCRIMINATOR)?;
+        ctx.accounts.sale.paused = true;
+        msg!("Sale paused");
+        Ok(())
+    }
+
+    pub fn resume(ctx: Context<SetPause>) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::Resume::DISCRIMINATOR)?;
+        ctx.accounts.sale.paused = false;
+        msg!("Sale resumed");
         Ok(())
     }
 
+    /// Cierra la venta: exige que no queden compras en escrow, devuelve el
+    /// inventario no vendido a la autoridad, barre cualquier saldo residual del
+    /// vault de pagos al vendedor y cierra los vaults para recuperar renta.
+    /// La cuenta de la venta se conserva porque ancla el registro de posiciones que
+    /// sigue usando el mercado secundario.
     pub fn close(ctx: Context<Close>) -> Result<()> {
-        // Establecer activo como falso y cerrar cuenta para recuperar renta
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::Close::DISCRIMINATOR)?;
+        let sale = &ctx.accounts.sale;
+        require!(!sale.closed, Err::AlreadyClosed);
+        require!(sale.pending_purchases == 0, Err::PendingPurchases);
+
+        let mint_key = sale.mint;
+        let seeds = &[b"sale".as_ref(), mint_key.as_ref(), &[sale.bump]];
+        let signer = &[&seeds[..]];
+
+        // PASO 1: Devolver el inventario no vendido
+        let unsold = ctx.accounts.token_vault.amount;
+        if unsold > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.token_vault.to_account_info(),
+                to: ctx.accounts.authority_token_account.to_account_info(),
+                authority: ctx.accounts.sale.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts,
+                signer,
+            );
+            token::transfer(cpi_ctx, unsold)?;
+        }
+
+        // PASO 2: Sin compras pendientes, cualquier saldo del vault de pagos es un
+        // depósito directo; se envía al vendedor para que el vault pueda cerrarse
+        let residual = ctx.accounts.payment_vault.amount;
+        if residual > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.payment_vault.to_account_info(),
+                to: ctx.accounts.seller_token_account.to_account_info(),
+                authority: ctx.accounts.sale.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts,
+                signer,
+            );
+            token::transfer(cpi_ctx, residual)?;
+        }
+
+        // PASO 3: Cerrar los vaults
+        for vault in [
+            ctx.accounts.token_vault.to_account_info(),
+            ctx.accounts.payment_vault.to_account_info(),
+        ] {
+            let cpi_accounts = CloseAccount {
+                account: vault,
+                destination: ctx.accounts.authority.to_account_info(),
+                authority: ctx.accounts.sale.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts,
+                signer,
+            );
+            token::close_account(cpi_ctx)?;
+        }
+
+        let sale = &mut ctx.accounts.sale;
+        sale.active = false;
+        sale.paused = false;
+        sale.closed = true;
+        let raised = sale.price.checked_mul(sale.sold as u64).ok_or(Err::Overflow)?;
+
+        emit!(SaleClosed {
+            sale: sale.key(),
+            mint: sale.mint,
+            total_sold: sale.sold,
+            price: sale.price,
+            raised,
+            unsold_returned: unsold,
+            closed_at: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Sale closed - {} sold, {} returned to authority", sale.sold, unsold);
         Ok(())
     }
 }
 
+/// Reporta `sale.sold` al TrailerAsset firmando con el PDA de la venta,
+/// que debe estar registrado como `market_authority` del trailer
+fn sync_tokens_sold<'info>(
+    sale: &Account<'info, Sale>,
+    trailer_asset: &Account<'info, TrailerAsset>,
+    series: &Account<'info, Series>,
+    asset_nft_program: &Program<'info, AssetNft>,
+    guardian_config: &Account<'info, GuardianConfig>,
+) -> Result<()> {
+    let seeds = &[b"sale".as_ref(), sale.mint.as_ref(), &[sale.bump]];
+    let signer = &[&seeds[..]];
+
+    let cpi_accounts = asset_nft::cpi::accounts::UpdateTokensSold {
+        market_authority: sale.to_account_info(),
+        trailer_asset: trailer_asset.to_account_info(),
+        series: series.to_account_info(),
+        guardian_config: guardian_config.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(asset_nft_program.to_account_info(), cpi_accounts, signer);
+    asset_nft::cpi::update_tokens_sold(cpi_ctx, sale.sold)
+}
+
 #[derive(Accounts)]
 pub struct InitSale<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
-    #[account(init, payer = authority, space = 8 + 32 + 8 + 2 + 2 + 1 + 1, seeds = [b"sale"], bump)]
+    #[account(init, payer = authority, space = 8 + Sale::INIT_SPACE, seeds = [b"sale", mint.key().as_ref()], bump)]
     pub sale: Account<'info, Sale>,
+    /// Mint de los tokens fraccionados del trailer
+    pub mint: Account<'info, Mint>,
+    /// Solo la autoridad del trailer puede abrir la venta de su mint
+    #[account(
+        seeds = [b"trailer", mint.key().as_ref()],
+        bump = trailer_asset.bump,
+        seeds::program = asset_nft::ID,
+        constraint = trailer_asset.mint == mint.key() @ Err::TrailerMismatch,
+        constraint = trailer_asset.authority == authority.key() @ Err::Unauthorized
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+    /// Mint de la moneda de pago (USDC)
+    pub payment_mint: Account<'info, Mint>,
+    /// Cuenta USDC del vendedor que recibe los fondos al finalizar cada compra
+    #[account(constraint = seller_token_account.mint == payment_mint.key())]
+    pub seller_token_account: Account<'info, TokenAccount>,
+    /// Escrow de pagos durante el período de desistimiento
+    #[account(
+        init,
+        payer = authority,
+        token::mint = payment_mint,
+        token::authority = sale,
+        seeds = [b"payment_vault", sale.key().as_ref()],
+        bump
+    )]
+    pub payment_vault: Account<'info, TokenAccount>,
+    /// Inventario de tokens a la venta, depositado por la autoridad
+    #[account(
+        init,
+        payer = authority,
+        token::mint = mint,
+        token::authority = sale,
+        seeds = [b"token_vault", sale.key().as_ref()],
+        bump
+    )]
+    pub token_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
 }
 
 #[derive(Accounts)]
 pub struct Buy<'info> {
-    #[account(mut)]
+    #[account(mut, seeds = [b"sale", sale.mint.as_ref()], bump = sale.bump)]
     pub sale: Account<'info, Sale>,
     #[account(mut)]
     pub buyer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + Position::INIT_SPACE,
+        seeds = [b"position", sale.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, Position>,
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + Purchase::INIT_SPACE,
+        seeds = [b"purchase", sale.key().as_ref(), sale.next_purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub purchase: Account<'info, Purchase>,
+    #[account(mut, constraint = buyer_token_account.mint == sale.payment_mint)]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [b"payment_vault", sale.key().as_ref()], bump)]
+    pub payment_vault: Account<'info, TokenAccount>,
+    #[account(seeds = [b"token_vault", sale.key().as_ref()], bump)]
+    pub token_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"trailer", sale.mint.as_ref()],
+        bump = trailer_asset.bump,
+        seeds::program = asset_nft::ID
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+    #[account(
+        mut,
+        seeds = [b"series", trailer_asset.series.as_bytes()],
+        bump = series.bump,
+        seeds::program = asset_nft::ID
+    )]
+    pub series: Account<'info, Series>,
+    pub asset_nft_program: Program<'info, AssetNft>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CancelPurchase<'info> {
+    #[account(mut, seeds = [b"sale", sale.mint.as_ref()], bump = sale.bump)]
+    pub sale: Account<'info, Sale>,
     #[account(mut)]
+    pub buyer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"position", sale.key().as_ref(), buyer.key().as_ref()],
+        bump = position.bump
+    )]
+    pub position: Account<'info, Position>,
+    #[account(mut, has_one = sale, has_one = buyer, close = buyer)]
+    pub purchase: Account<'info, Purchase>,
+    #[account(mut, constraint = buyer_token_account.mint == sale.payment_mint)]
     pub buyer_token_account: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [b"payment_vault", sale.key().as_ref()], bump)]
+    pub payment_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"trailer", sale.mint.as_ref()],
+        bump = trailer_asset.bump,
+        seeds::program = asset_nft::ID
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+    #[account(
+        mut,
+        seeds = [b"series", trailer_asset.series.as_bytes()],
+        bump = series.bump,
+        seeds::program = asset_nft::ID
+    )]
+    pub series: Account<'info, Series>,
+    pub asset_nft_program: Program<'info, AssetNft>,
+    pub token_program: Program<'info, Token>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizePurchase<'info> {
+    #[account(mut, seeds = [b"sale", sale.mint.as_ref()], bump = sale.bump)]
+    pub sale: Account<'info, Sale>,
+    /// CHECK: Comprador original, recibe la renta de la cuenta de compra
     #[account(mut)]
+    pub buyer: UncheckedAccount<'info>,
+    #[account(mut, has_one = sale, has_one = buyer, close = buyer)]
+    pub purchase: Account<'info, Purchase>,
+    #[account(
+        mut,
+        constraint = buyer_asset_account.mint == sale.mint,
+        constraint = buyer_asset_account.owner == buyer.key()
+    )]
+    pub buyer_asset_account: Account<'info, TokenAccount>,
+    #[account(mut, address = sale.seller_token_account)]
     pub seller_token_account: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [b"payment_vault", sale.key().as_ref()], bump)]
+    pub payment_vault: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [b"token_vault", sale.key().as_ref()], bump)]
+    pub token_vault: Account<'info, TokenAccount>,
     pub token_program: Program<'info, Token>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct TransferPosition<'info> {
+    /// PDA del mercado secundario que autoriza el movimiento
+    #[account(seeds = [b"market_authority"], bump, seeds::program = SECONDARY_MARKET_ID)]
+    pub market_authority: Signer<'info>,
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub sale: Account<'info, Sale>,
+    #[account(
+        mut,
+        seeds = [b"position", sale.key().as_ref(), owner.key().as_ref()],
+        bump = from_position.bump,
+        has_one = owner
+    )]
+    pub from_position: Account<'info, Position>,
+    /// CHECK: Solo se usa como semilla y dueño de la posición destino
+    pub recipient: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + Position::INIT_SPACE,
+        seeds = [b"position", sale.key().as_ref(), recipient.key().as_ref()],
+        bump
+    )]
+    pub to_position: Account<'info, Position>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut, has_one = authority)]
+    pub sale: Account<'info, Sale>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
 }
 
 #[derive(Accounts)]
 pub struct Close<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
-    #[account(mut, has_one = authority, close = authority)]
+    #[account(mut, has_one = authority, seeds = [b"sale", sale.mint.as_ref()], bump = sale.bump)]
     pub sale: Account<'info, Sale>,
+    #[account(
+        mut,
+        constraint = authority_token_account.mint == sale.mint,
+        constraint = authority_token_account.owner == authority.key()
+    )]
+    pub authority_token_account: Account<'info, TokenAccount>,
+    #[account(mut, address = sale.seller_token_account)]
+    pub seller_token_account: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [b"payment_vault", sale.key().as_ref()], bump)]
+    pub payment_vault: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [b"token_vault", sale.key().as_ref()], bump)]
+    pub token_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
 }
 
 #[account]
+#[derive(InitSpace)]
 pub struct Sale {
     pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub payment_mint: Pubkey,
+    pub seller_token_account: Pubkey,
     pub price: u64,
     pub total: u16,
     pub sold: u16,
+    /// Tokens reservados por compras aún en período de desistimiento
+    pub pending_tokens: u16,
+    pub pending_purchases: u32,
+    pub next_purchase_id: u64,
+    /// Máximo de tokens por wallet (0 = sin límite)
+    pub max_per_wallet: u16,
+    /// Máximo porcentaje del supply por wallet en basis points (0 = sin límite)
+    pub max_wallet_bps: u16,
+    /// Segundos durante los que el comprador puede cancelar (0 = finalización inmediata)
+    pub cooling_off_period: i64,
     pub active: bool,
+    /// Pausa temporal de compras (ver `pause`/`resume`)
+    pub paused: bool,
+    pub closed: bool,
+    pub bump: u8,
+}
+
+impl Sale {
+    /// Límite efectivo por wallet: el menor entre el tope absoluto y el porcentaje del supply
+    pub fn wallet_cap(&self) -> u16 {
+        let mut cap = self.total;
+        if self.max_per_wallet > 0 {
+            cap = cap.min(self.max_per_wallet);
+        }
+        if self.max_wallet_bps > 0 {
+            let by_share = (self.total as u64 * self.max_wallet_bps as u64) / BPS_DENOMINATOR;
+            cap = cap.min(by_share as u16);
+        }
+        cap
+    }
+
+    pub fn check_wallet_limit(&self, holding: u16) -> Result<()> {
+        require!(holding <= self.wallet_cap(), Err::WalletLimitExceeded);
+        Ok(())
+    }
+}
+
+/// Posición acumulada de una wallet en una venta
+#[account]
+#[derive(InitSpace)]
+pub struct Position {
+    pub owner: Pubkey,
+    pub sale: Pubkey,
+    pub amount: u16,
     pub bump: u8,
 }
 
+/// Compra en escrow pendiente de finalización
+#[account]
+#[derive(InitSpace)]
+pub struct Purchase {
+    pub sale: Pubkey,
+    pub buyer: Pubkey,
+    pub id: u64,
+    pub amount: u16,
+    pub cost: u64,
+    pub purchased_at: i64,
+    pub release_at: i64,
+    pub bump: u8,
+}
+
+/// Resumen final emitido al cerrar una venta
+#[event]
+pub struct SaleClosed {
+    pub sale: Pubkey,
+    pub mint: Pubkey,
+    pub total_sold: u16,
+    pub price: u64,
+    pub raised: u64,
+    pub unsold_returned: u64,
+    pub closed_at: i64,
+}
+
 #[error_code]
 pub enum Err {
     #[msg("Venta no activa")]
@@ -104,4 +697,26 @@ pub enum Err {
     NotEnough,
     #[msg("Desbordamiento en el calculo")]
     Overflow,
+    #[msg("Limite de tenencia por wallet excedido")]
+    WalletLimitExceeded,
+    #[msg("Limite invalido")]
+    InvalidLimit,
+    #[msg("Periodo de desistimiento invalido")]
+    InvalidCoolingOff,
+    #[msg("El periodo de desistimiento ya vencio")]
+    CoolingOffExpired,
+    #[msg("El periodo de desistimiento sigue vigente")]
+    CoolingOffActive,
+    #[msg("Venta en pausa")]
+    Paused,
+    #[msg("Hay compras pendientes de finalizar o cancelar")]
+    PendingPurchases,
+    #[msg("La venta ya fue cerrada")]
+    AlreadyClosed,
+    #[msg("El trailer no esta en etapa de recaudacion")]
+    NotFundraising,
+    #[msg("El trailer no corresponde al mint de la venta")]
+    TrailerMismatch,
+    #[msg("Solo la autoridad del trailer puede abrir la venta")]
+    Unauthorized,
 }
@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use asset_nft::{ClaimStatus, GuardianConfig, InsuranceClaim, Lease, ProceedsRoute, TrailerAsset};
 
 declare_id!("DVfDdWLdsin4LGgor4B1nNQTSe4oi5F4cfmRVafpeMog");
 
@@ -7,50 +9,295 @@ pub mod returns_distribution {
     use super::*;
 
     pub fn init_pool(ctx: Context<InitPool>, apy: u16) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::InitPool::DISCRIMINATOR)?;
         let pool = &mut ctx.accounts.pool;
         pool.authority = ctx.accounts.authority.key();
+        pool.trailer_asset = ctx.accounts.trailer_asset.key();
         pool.apy = apy;
         pool.total = 0;
         pool.bump = ctx.bumps.pool;
         Ok(())
     }
 
+    /// Crea el vault de tokens del pool, donde entran las indemnizaciones y los pagos de renta
+    pub fn init_pool_vault(ctx: Context<InitPoolVault>) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::InitPoolVault::DISCRIMINATOR)?;
+        msg!("Vault del pool creado: {}", ctx.accounts.pool_vault.key());
+        Ok(())
+    }
+
     pub fn deposit(ctx: Context<Deposit>, amt: u64) -> Result<()> {
-        ctx.accounts.pool.total += amt;
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::Deposit::DISCRIMINATOR)?;
+        let pool = &mut ctx.accounts.pool;
+        pool.total = pool.total.checked_add(amt).ok_or(Err::Overflow)?;
+        Ok(())
+    }
+
+    /// Actualiza el APY de referencia usado para calcular retornos
+    pub fn set_apy(ctx: Context<SetApy>, apy: u16) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::SetApy::DISCRIMINATOR)?;
+        ctx.accounts.pool.apy = apy;
+        msg!("APY del pool actualizado: {}", apy);
         Ok(())
     }
 
     pub fn claim(ctx: Context<Claim>, tokens: u16) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::Claim::DISCRIMINATOR)?;
+        require!(
+            ctx.accounts.trailer_asset.status.accepts_distributions(),
+            Err::DistributionsHalted
+        );
         let pool = &ctx.accounts.pool;
         let returns = (tokens as u64) * (pool.apy as u64) / 100;
         require!(returns <= pool.total, Err::InsufficientFunds);
         Ok(())
     }
+
+    /// Cobra un pago de renta en el vault del pool, lo suma al pool y concilia contra la renta esperada.
+    /// Se acredita lo que efectivamente entra al vault, no lo declarado.
+    pub fn record_lease_payment(ctx: Context<RecordLeasePayment>, amount: u64) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::RecordLeasePayment::DISCRIMINATOR)?;
+        let now = Clock::get()?.unix_timestamp;
+
+        let before = ctx.accounts.pool_vault.amount;
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.payer_token_account.to_account_info(),
+            to: ctx.accounts.pool_vault.to_account_info(),
+            authority: ctx.accounts.payer.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        token::transfer(cpi_ctx, amount)?;
+        ctx.accounts.pool_vault.reload()?;
+        let amount = ctx.accounts.pool_vault.amount.checked_sub(before).ok_or(Err::Overflow)?;
+
+        let ledger = &mut ctx.accounts.ledger;
+        if ledger.lease == Pubkey::default() {
+            ledger.lease = ctx.accounts.lease.key();
+            ledger.pool = ctx.accounts.pool.key();
+            ledger.bump = ctx.bumps.ledger;
+        }
+        ledger.received = ledger.received.checked_add(amount).ok_or(Err::Overflow)?;
+        ledger.last_payment_at = now;
+        let pool = &mut ctx.accounts.pool;
+        pool.total = pool.total.checked_add(amount).ok_or(Err::Overflow)?;
+
+        msg!("Pago de renta registrado: {}", amount);
+        reconcile(ledger, &ctx.accounts.lease, now);
+        Ok(())
+    }
+
+    /// Suma al pool una indemnizacion liquidada a favor de los tenedores; cada siniestro solo se deposita una vez.
+    /// Los fondos ya estan en el vault: `settle_claim` los transfiere desde el asegurador.
+    pub fn deposit_claim_proceeds(ctx: Context<DepositClaimProceeds>) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::DepositClaimProceeds::DISCRIMINATOR)?;
+        let claim = &ctx.accounts.claim;
+        require!(
+            claim.status == ClaimStatus::Settled && claim.route == ProceedsRoute::Holders,
+            Err::ClaimNotPayable
+        );
+
+        let deposit = &mut ctx.accounts.claim_deposit;
+        deposit.claim = claim.key();
+        deposit.pool = ctx.accounts.pool.key();
+        deposit.amount = claim.settlement_amount;
+        deposit.deposited_at = Clock::get()?.unix_timestamp;
+        deposit.bump = ctx.bumps.claim_deposit;
+
+        let pool = &mut ctx.accounts.pool;
+        pool.total = pool.total.checked_add(claim.settlement_amount).ok_or(Err::Overflow)?;
+
+        msg!("Indemnizacion depositada en el pool: {}", claim.settlement_amount);
+        Ok(())
+    }
+
+    /// Recalcula los atrasos de un contrato sin registrar pagos; cualquiera puede ejecutarlo
+    pub fn reconcile_lease(ctx: Context<ReconcileLease>) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::ReconcileLease::DISCRIMINATOR)?;
+        let now = Clock::get()?.unix_timestamp;
+        reconcile(&mut ctx.accounts.ledger, &ctx.accounts.lease, now);
+        Ok(())
+    }
+}
+
+fn reconcile(ledger: &mut LeaseLedger, lease: &Lease, now: i64) {
+    let expected = lease.expected_rent_at(now);
+    ledger.expected = expected;
+    ledger.arrears = expected.saturating_sub(ledger.received);
+    ledger.reconciled_at = now;
+
+    if ledger.arrears > 0 {
+        emit!(LeaseInArrears {
+            lease: ledger.lease,
+            expected,
+            received: ledger.received,
+            arrears: ledger.arrears,
+            at: now,
+        });
+    }
 }
 
 #[derive(Accounts)]
 pub struct InitPool<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
-    #[account(init, payer = authority, space = 8 + 32 + 2 + 8 + 1, seeds = [b"pool"], bump)]
+    #[account(init, payer = authority, space = 8 + 32 + 32 + 2 + 8 + 1, seeds = [b"pool", trailer_asset.key().as_ref()], bump)]
     pub pool: Account<'info, Pool>,
+    #[account(
+        seeds = [b"trailer", trailer_asset.mint.as_ref()],
+        bump = trailer_asset.bump,
+        seeds::program = asset_nft::ID,
+        constraint = trailer_asset.authority == authority.key() @ Err::Unauthorized
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
     pub system_program: Program<'info, System>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitPoolVault<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(has_one = authority)]
+    pub pool: Account<'info, Pool>,
+    pub payment_mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"pool_vault", pool.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = pool
+    )]
+    pub pool_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
 }
 
 #[derive(Accounts)]
 pub struct Deposit<'info> {
     #[account(mut)]
     pub pool: Account<'info, Pool>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetApy<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut, has_one = authority)]
+    pub pool: Account<'info, Pool>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
 }
 
 #[derive(Accounts)]
 pub struct Claim<'info> {
+    #[account(has_one = trailer_asset)]
     pub pool: Account<'info, Pool>,
+    pub trailer_asset: Account<'info, TrailerAsset>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RecordLeasePayment<'info> {
+    /// Quien paga la renta (el arrendatario o la autoridad en su nombre)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    #[account(constraint = lease.trailer_asset == pool.trailer_asset @ Err::LeaseMismatch)]
+    pub lease: Account<'info, Lease>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + LeaseLedger::INIT_SPACE,
+        seeds = [b"lease_ledger", lease.key().as_ref()],
+        bump
+    )]
+    pub ledger: Account<'info, LeaseLedger>,
+    #[account(mut, constraint = payer_token_account.owner == payer.key())]
+    pub payer_token_account: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [b"pool_vault", pool.key().as_ref()], bump)]
+    pub pool_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ReconcileLease<'info> {
+    #[account(address = ledger.lease)]
+    pub lease: Account<'info, Lease>,
+    #[account(mut, seeds = [b"lease_ledger", lease.key().as_ref()], bump = ledger.bump)]
+    pub ledger: Account<'info, LeaseLedger>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct DepositClaimProceeds<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(mut, has_one = authority)]
+    pub pool: Account<'info, Pool>,
+    #[account(constraint = claim.trailer_asset == pool.trailer_asset @ Err::ClaimMismatch)]
+    pub claim: Account<'info, InsuranceClaim>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ClaimDeposit::INIT_SPACE,
+        seeds = [b"claim_deposit", claim.key().as_ref()],
+        bump
+    )]
+    pub claim_deposit: Account<'info, ClaimDeposit>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+/// Conciliación de los pagos de un contrato de arrendamiento
+#[account]
+#[derive(InitSpace)]
+pub struct LeaseLedger {
+    pub lease: Pubkey,
+    pub pool: Pubkey,
+    pub expected: u64,
+    pub received: u64,
+    pub arrears: u64,
+    pub last_payment_at: i64,
+    pub reconciled_at: i64,
+    pub bump: u8,
+}
+
+/// Marca que la indemnizacion de un siniestro ya entro al pool
+#[account]
+#[derive(InitSpace)]
+pub struct ClaimDeposit {
+    pub claim: Pubkey,
+    pub pool: Pubkey,
+    pub amount: u64,
+    pub deposited_at: i64,
+    pub bump: u8,
+}
+
+#[event]
+pub struct LeaseInArrears {
+    pub lease: Pubkey,
+    pub expected: u64,
+    pub received: u64,
+    pub arrears: u64,
+    pub at: i64,
 }
 
 #[account]
 pub struct Pool {
     pub authority: Pubkey,
+    pub trailer_asset: Pubkey,
     pub apy: u16,
     pub total: u64,
     pub bump: u8,
@@ -60,4 +307,16 @@ pub struct Pool {
 pub enum Err {
     #[msg("Fondos insuficientes")]
     InsufficientFunds,
+    #[msg("El trailer no admite distribuciones en su estado actual")]
+    DistributionsHalted,
+    #[msg("El contrato no pertenece al trailer del pool")]
+    LeaseMismatch,
+    #[msg("Desbordamiento en el calculo")]
+    Overflow,
+    #[msg("El siniestro no pertenece al trailer del pool")]
+    ClaimMismatch,
+    #[msg("El siniestro no esta liquidado a favor de los tenedores")]
+    ClaimNotPayable,
+    #[msg("Solo la autoridad del trailer puede crear su pool")]
+    Unauthorized,
 }
@@ -1,15 +1,23 @@
+#![allow(clippy::too_many_arguments)]
+
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 use anchor_spl::associated_token::AssociatedToken;
+use primary_market::program::PrimaryMarket;
+use primary_market::{Position, Sale};
+use asset_nft::{GuardianConfig, TrailerAsset};
 
 declare_id!("DakwaYqG3tV9Jjgy5GokyQJd3JWb74Qx66JHqbZicsZX");
 
 // Precio mínimo calculado: (Costo 0.12 + Penalización 0.025) / (1 - 0.03 fee) ≈ 0.1495 SOL
 const MINIMUM_PRICE: u64 = 149_500_000; // ~0.1495 SOL en lamports
 const MARKETPLACE_FEE_BPS: u64 = 300; // 3% = 300 basis points
+const TREASURY_FEE_SHARE_BPS: u64 = 5000; // 50% de la comisión va a la tesorería de la serie
 const EARLY_SALE_PENALTY: u64 = 25_000_000; // 0.025 SOL en lamports
 const TERM_YEARS: i64 = 5; // 5 años de vigencia
 const SECONDS_PER_YEAR: i64 = 365 * 24 * 60 * 60;
+const MARKET_AUTHORITY_SEED: &[u8] = b"market_authority";
+const MAX_FX_RATE_AGE: i64 = 24 * 60 * 60; // Pasado 1 día la cotización se reporta como desactualizada
 
 #[program]
 pub mod secondary_market {
@@ -18,14 +26,30 @@ pub mod secondary_market {
     /// Crea un listing de NFT para reventa
     /// El vendedor transfiere el NFT a un escrow (PDA) hasta que se venda
     pub fn list(ctx: Context<List>, price: u64, purchase_date: i64) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::List::DISCRIMINATOR)?;
         let listing = &mut ctx.accounts.listing;
         let seller_token_account = &ctx.accounts.seller_token_account;
 
         // Validar precio mínimo
         require!(price >= MINIMUM_PRICE, ErrorCode::PriceTooLow);
 
-        // Validar que el vendedor posea el NFT (balance = 1)
-        require!(seller_token_account.amount == 1, ErrorCode::NFTNotOwned);
+        // El NAV por token de la última tasación actúa como precio piso. Está en
+        // unidades de la moneda de pago (USDC), así que se convierte a lamports.
+        // Si el oráculo se atrasa se usa la última cotización publicada para no
+        // detener los listados
+        let fx_rate = &ctx.accounts.fx_rate;
+        if fx_rate.is_stale(Clock::get()?.unix_timestamp) {
+            msg!("FX rate is stale, using last rate from {}", fx_rate.updated_at);
+        }
+        let nav_lamports = fx_rate.to_lamports(ctx.accounts.trailer_asset.nav_per_token())?;
+        require!(price >= nav_lamports, ErrorCode::PriceBelowNav);
+
+        // Validar que el trailer admita negociación secundaria en su estado actual
+        require!(ctx.accounts.trailer_asset.status.is_tradable(), ErrorCode::NotTradable);
+        require!(!ctx.accounts.trailer_asset.is_locked, ErrorCode::AssetLocked);
+
+        // Validar que el vendedor posea al menos una unidad del trailer
+        require!(seller_token_account.amount >= 1, ErrorCode::NFTNotOwned);
 
         // Validar que el token account pertenece al vendedor
         require!(seller_token_account.owner == ctx.accounts.seller.key(), ErrorCode::InvalidOwner);
@@ -40,9 +64,31 @@ pub mod secondary_market {
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
         token::transfer(cpi_ctx, 1)?;
 
-        // PASO 2: Guardar datos del listing
+        // PASO 2: Mover la posición del vendedor al escrow del listing
+        let authority_seeds = &[MARKET_AUTHORITY_SEED, &[ctx.bumps.market_authority]];
+        let authority_signer = &[&authority_seeds[..]];
+        let cpi_accounts = primary_market::cpi::accounts::TransferPosition {
+            market_authority: ctx.accounts.market_authority.to_account_info(),
+            owner: ctx.accounts.seller.to_account_info(),
+            payer: ctx.accounts.seller.to_account_info(),
+            sale: ctx.accounts.sale.to_account_info(),
+            from_position: ctx.accounts.seller_position.to_account_info(),
+            recipient: listing.to_account_info(),
+            to_position: ctx.accounts.listing_position.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            guardian_config: ctx.accounts.guardian_config.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.primary_market_program.to_account_info(),
+            cpi_accounts,
+            authority_signer,
+        );
+        primary_market::cpi::transfer_position(cpi_ctx, 1)?;
+
+        // PASO 3: Guardar datos del listing
         listing.seller = ctx.accounts.seller.key();
         listing.nft_mint = ctx.accounts.nft_mint.key();
+        listing.sale = ctx.accounts.sale.key();
         listing.price = price;
         listing.purchase_date = purchase_date;
         listing.listed_at = Clock::get()?.unix_timestamp;
@@ -57,10 +103,13 @@ pub mod secondary_market {
     /// Compra un NFT del mercado secundario
     /// Transferencia atómica: NFT al comprador, SOL al vendedor (menos fees)
     pub fn buy(ctx: Context<Buy>) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::Buy::DISCRIMINATOR)?;
         let listing = &ctx.accounts.listing;
 
         // Validar que el listing esté activo
         require!(listing.active, ErrorCode::NotActive);
+        require!(ctx.accounts.trailer_asset.status.is_tradable(), ErrorCode::NotTradable);
+        require!(!ctx.accounts.trailer_asset.is_locked, ErrorCode::AssetLocked);
 
         // Validar que el NFT esté en el escrow
         require!(ctx.accounts.escrow_token_account.amount == 1, ErrorCode::NFTNotOwned);
@@ -83,6 +132,12 @@ pub mod secondary_market {
             .checked_mul(MARKETPLACE_FEE_BPS).ok_or(ErrorCode::ArithmeticError)?
             .checked_div(10000).ok_or(ErrorCode::ArithmeticError)?;
 
+        let treasury_fee = marketplace_fee
+            .checked_mul(TREASURY_FEE_SHARE_BPS).ok_or(ErrorCode::ArithmeticError)?
+            .checked_div(10000).ok_or(ErrorCode::ArithmeticError)?;
+        let platform_fee = marketplace_fee
+            .checked_sub(treasury_fee).ok_or(ErrorCode::ArithmeticError)?;
+
         // Calcular lo que recibe el vendedor
         let seller_proceeds = price_after_penalty
             .checked_sub(marketplace_fee).ok_or(ErrorCode::ArithmeticError)?;
@@ -90,6 +145,7 @@ pub mod secondary_market {
         msg!("Precio: {} lamports", price);
         msg!("Penalización: {} lamports", penalty);
         msg!("Comisión marketplace: {} lamports", marketplace_fee);
+        msg!("Tesorería de gobernanza: {} lamports", treasury_fee);
         msg!("Vendedor recibe: {} lamports", seller_proceeds);
 
         // PASO 1: Transferir SOL del comprador al vendedor
@@ -111,7 +167,7 @@ pub mod secondary_market {
         let transfer_fee_ix = anchor_lang::solana_program::system_instruction::transfer(
             &ctx.accounts.buyer.key(),
             &ctx.accounts.platform_wallet.key(),
-            marketplace_fee,
+            platform_fee,
         );
         anchor_lang::solana_program::program::invoke(
             &transfer_fee_ix,
@@ -122,8 +178,22 @@ pub mod secondary_market {
             ],
         )?;
 
+        // PASO 2b: Parte de la comisión a la tesorería de gobernanza de la serie
+        let transfer_treasury_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.buyer.key(),
+            &ctx.accounts.treasury.key(),
+            treasury_fee,
+        );
+        anchor_lang::solana_program::program::invoke(
+            &transfer_treasury_ix,
+            &[
+                ctx.accounts.buyer.to_account_info(),
+                ctx.accounts.treasury.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
         // PASO 3: Transferir NFT del escrow al comprador usando el PDA como autoridad
-        let listing_key = listing.key();
         let nft_mint_key = listing.nft_mint.key();
         let seeds = &[
             b"listing",
@@ -146,7 +216,28 @@ pub mod secondary_market {
             ctx.accounts.buyer.key()
         );
 
-        // PASO 4: Marcar listing como inactivo
+        // PASO 4: Mover la posición al comprador (aplica el límite de concentración)
+        let authority_seeds = &[MARKET_AUTHORITY_SEED, &[ctx.bumps.market_authority]];
+        let signers = &[&seeds[..], &authority_seeds[..]];
+        let cpi_accounts = primary_market::cpi::accounts::TransferPosition {
+            market_authority: ctx.accounts.market_authority.to_account_info(),
+            owner: ctx.accounts.listing.to_account_info(),
+            payer: ctx.accounts.buyer.to_account_info(),
+            sale: ctx.accounts.sale.to_account_info(),
+            from_position: ctx.accounts.listing_position.to_account_info(),
+            recipient: ctx.accounts.buyer.to_account_info(),
+            to_position: ctx.accounts.buyer_position.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            guardian_config: ctx.accounts.guardian_config.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.primary_market_program.to_account_info(),
+            cpi_accounts,
+            signers,
+        );
+        primary_market::cpi::transfer_position(cpi_ctx, 1)?;
+
+        // PASO 5: Marcar listing como inactivo
         let listing_mut = &mut ctx.accounts.listing;
         listing_mut.active = false;
 
@@ -156,39 +247,162 @@ pub mod secondary_market {
     /// Cancela un listing activo y devuelve el NFT al vendedor
     /// Solo el vendedor puede cancelar su propio listing
     pub fn cancel(ctx: Context<Cancel>) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::Cancel::DISCRIMINATOR)?;
         // Validar que esté activo
         require!(ctx.accounts.listing.active, ErrorCode::NotActive);
 
-        // PASO 1: Devolver NFT del escrow al vendedor
-        let listing = &ctx.accounts.listing;
-        let nft_mint_key = listing.nft_mint.key();
-        let seeds = &[
-            b"listing",
-            listing.seller.as_ref(),
-            nft_mint_key.as_ref(),
-            &[listing.bump],
-        ];
-        let signer = &[&seeds[..]];
+        // PASO 1 y 2: Devolver NFT y posición al vendedor
+        let accounts = &ctx.accounts;
+        return_escrow_to_seller(
+            &accounts.listing,
+            &accounts.escrow_token_account,
+            &accounts.seller_token_account,
+            accounts.seller.to_account_info(),
+            accounts.seller.to_account_info(),
+            &accounts.sale,
+            &accounts.listing_position,
+            accounts.seller_position.to_account_info(),
+            accounts.market_authority.to_account_info(),
+            ctx.bumps.market_authority,
+            &accounts.token_program,
+            &accounts.primary_market_program,
+            &accounts.system_program,
+            &accounts.guardian_config,
+        )?;
 
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.escrow_token_account.to_account_info(),
-            to: ctx.accounts.seller_token_account.to_account_info(),
-            authority: ctx.accounts.listing.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, 1)?;
+        // PASO 3: Marcar listing como inactivo
+        let listing_mut = &mut ctx.accounts.listing;
+        listing_mut.active = false;
 
-        msg!("NFT devuelto al vendedor desde escrow");
+        msg!("Listing cancelado - NFT: {}", listing_mut.nft_mint);
+
+        Ok(())
+    }
+
+    /// Libera un listing cuyo trailer fue liquidado o está en redención para que
+    /// el vendedor pueda reclamar su parte. Cualquiera puede ejecutarlo; si el
+    /// escrow quedó congelado, el lock manager debe descongelarlo antes con
+    /// `asset_nft::thaw_holder`.
+    pub fn release_escrow(ctx: Context<ReleaseEscrow>) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::ReleaseEscrow::DISCRIMINATOR)?;
+        require!(ctx.accounts.listing.active, ErrorCode::NotActive);
+        require!(ctx.accounts.trailer_asset.status.is_winding_down(), ErrorCode::NotWindingDown);
+
+        let accounts = &ctx.accounts;
+        return_escrow_to_seller(
+            &accounts.listing,
+            &accounts.escrow_token_account,
+            &accounts.seller_token_account,
+            accounts.seller.to_account_info(),
+            accounts.payer.to_account_info(),
+            &accounts.sale,
+            &accounts.listing_position,
+            accounts.seller_position.to_account_info(),
+            accounts.market_authority.to_account_info(),
+            ctx.bumps.market_authority,
+            &accounts.token_program,
+            &accounts.primary_market_program,
+            &accounts.system_program,
+            &accounts.guardian_config,
+        )?;
 
-        // PASO 2: Marcar listing como inactivo
         let listing_mut = &mut ctx.accounts.listing;
         listing_mut.active = false;
 
-        msg!("Listing cancelado - NFT: {}", listing_mut.nft_mint);
+        msg!("Listing liberado por liquidación - NFT: {}", listing_mut.nft_mint);
 
         Ok(())
     }
+
+    /// Crea la cotización SOL/moneda de pago usada para el piso de NAV.
+    /// Quien la crea queda como autoridad que la actualiza.
+    /// Crea la cotización global; solo el admin del protocolo (el de la
+    /// configuración del guardián) puede hacerlo y designa al oráculo
+    pub fn init_fx_rate(
+        ctx: Context<InitFxRate>,
+        authority: Pubkey,
+        lamports_per_unit: u64,
+        unit_decimals: u8,
+    ) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::InitFxRate::DISCRIMINATOR)?;
+        require!(lamports_per_unit > 0 && unit_decimals <= 18, ErrorCode::InvalidFxRate);
+        ctx.accounts.fx_rate.set_inner(FxRate {
+            authority,
+            lamports_per_unit,
+            unit_decimals,
+            updated_at: Clock::get()?.unix_timestamp,
+            bump: ctx.bumps.fx_rate,
+        });
+        msg!("Cotización creada: {} lamports por unidad", lamports_per_unit);
+        Ok(())
+    }
+
+    pub fn set_fx_rate(ctx: Context<SetFxRate>, lamports_per_unit: u64) -> Result<()> {
+        ctx.accounts.guardian_config.check(&crate::ID, crate::instruction::SetFxRate::DISCRIMINATOR)?;
+        require!(lamports_per_unit > 0, ErrorCode::InvalidFxRate);
+        let fx_rate = &mut ctx.accounts.fx_rate;
+        fx_rate.lamports_per_unit = lamports_per_unit;
+        fx_rate.updated_at = Clock::get()?.unix_timestamp;
+        msg!("Cotización actualizada: {} lamports por unidad", lamports_per_unit);
+        Ok(())
+    }
+}
+
+/// Devuelve la unidad en escrow y su posición al vendedor firmando con el PDA del listing
+fn return_escrow_to_seller<'info>(
+    listing: &Account<'info, Listing>,
+    escrow_token_account: &Account<'info, TokenAccount>,
+    seller_token_account: &Account<'info, TokenAccount>,
+    seller: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    sale: &Account<'info, Sale>,
+    listing_position: &Account<'info, Position>,
+    seller_position: AccountInfo<'info>,
+    market_authority: AccountInfo<'info>,
+    market_authority_bump: u8,
+    token_program: &Program<'info, Token>,
+    primary_market_program: &Program<'info, PrimaryMarket>,
+    system_program: &Program<'info, System>,
+    guardian_config: &Account<'info, GuardianConfig>,
+) -> Result<()> {
+    let nft_mint_key = listing.nft_mint.key();
+    let seeds = &[
+        b"listing",
+        listing.seller.as_ref(),
+        nft_mint_key.as_ref(),
+        &[listing.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: escrow_token_account.to_account_info(),
+        to: seller_token_account.to_account_info(),
+        authority: listing.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer);
+    token::transfer(cpi_ctx, 1)?;
+
+    msg!("NFT devuelto al vendedor desde escrow");
+
+    let authority_seeds = &[MARKET_AUTHORITY_SEED, &[market_authority_bump]];
+    let signers = &[&seeds[..], &authority_seeds[..]];
+    let cpi_accounts = primary_market::cpi::accounts::TransferPosition {
+        market_authority,
+        owner: listing.to_account_info(),
+        payer,
+        sale: sale.to_account_info(),
+        from_position: listing_position.to_account_info(),
+        recipient: seller,
+        to_position: seller_position,
+        system_program: system_program.to_account_info(),
+        guardian_config: guardian_config.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        primary_market_program.to_account_info(),
+        cpi_accounts,
+        signers,
+    );
+    primary_market::cpi::transfer_position(cpi_ctx, 1)
 }
 
 #[derive(Accounts)]
@@ -227,9 +441,46 @@ pub struct List<'info> {
     )]
     pub escrow_token_account: Account<'info, TokenAccount>,
 
+    /// Venta primaria que lleva el registro de posiciones
+    #[account(constraint = sale.mint == nft_mint.key() @ ErrorCode::MintMismatch)]
+    pub sale: Account<'info, Sale>,
+
+    /// Trailer al que pertenece el mint listado
+    #[account(
+        seeds = [b"trailer", sale.mint.as_ref()],
+        bump = trailer_asset.bump,
+        seeds::program = asset_nft::ID
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    /// Cotización para expresar el NAV en lamports
+    #[account(seeds = [b"fx_rate"], bump = fx_rate.bump)]
+    pub fx_rate: Account<'info, FxRate>,
+
+    /// Posición del vendedor en la venta primaria
+    #[account(
+        mut,
+        seeds = [b"position", sale.key().as_ref(), seller.key().as_ref()],
+        bump = seller_position.bump,
+        seeds::program = primary_market::ID
+    )]
+    pub seller_position: Account<'info, Position>,
+
+    /// CHECK: Posición del escrow del listing, creada y validada por primary_market
+    #[account(mut)]
+    pub listing_position: UncheckedAccount<'info>,
+
+    /// CHECK: PDA del programa que autoriza los movimientos de posiciones en primary_market
+    #[account(seeds = [MARKET_AUTHORITY_SEED], bump)]
+    pub market_authority: UncheckedAccount<'info>,
+
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
+    pub primary_market_program: Program<'info, PrimaryMarket>,
     pub system_program: Program<'info, System>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
 }
 
 #[derive(Accounts)]
@@ -273,9 +524,49 @@ pub struct Buy<'info> {
     #[account(mut)]
     pub platform_wallet: AccountInfo<'info>,
 
+    /// Venta primaria asociada al listing
+    #[account(address = listing.sale)]
+    pub sale: Account<'info, Sale>,
+
+    /// Trailer al que pertenece el mint listado
+    #[account(
+        seeds = [b"trailer", sale.mint.as_ref()],
+        bump = trailer_asset.bump,
+        seeds::program = asset_nft::ID
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    /// CHECK: Tesorería de gobernanza de la serie del trailer, recibe parte de la comisión
+    #[account(
+        mut,
+        address = governance::series_treasury_address(&trailer_asset.series) @ ErrorCode::InvalidTreasury
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// Posición del escrow del listing
+    #[account(
+        mut,
+        seeds = [b"position", sale.key().as_ref(), listing.key().as_ref()],
+        bump = listing_position.bump,
+        seeds::program = primary_market::ID
+    )]
+    pub listing_position: Account<'info, Position>,
+
+    /// CHECK: Posición del comprador, creada y validada por primary_market
+    #[account(mut)]
+    pub buyer_position: UncheckedAccount<'info>,
+
+    /// CHECK: PDA del programa que autoriza los movimientos de posiciones en primary_market
+    #[account(seeds = [MARKET_AUTHORITY_SEED], bump)]
+    pub market_authority: UncheckedAccount<'info>,
+
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
+    pub primary_market_program: Program<'info, PrimaryMarket>,
     pub system_program: Program<'info, System>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
 }
 
 #[derive(Accounts)]
@@ -310,7 +601,128 @@ pub struct Cancel<'info> {
     )]
     pub seller_token_account: Account<'info, TokenAccount>,
 
+    /// Venta primaria asociada al listing
+    #[account(address = listing.sale)]
+    pub sale: Account<'info, Sale>,
+
+    /// Posición del escrow del listing
+    #[account(
+        mut,
+        seeds = [b"position", sale.key().as_ref(), listing.key().as_ref()],
+        bump = listing_position.bump,
+        seeds::program = primary_market::ID
+    )]
+    pub listing_position: Account<'info, Position>,
+
+    /// CHECK: Posición del vendedor, creada y validada por primary_market
+    #[account(mut)]
+    pub seller_position: UncheckedAccount<'info>,
+
+    /// CHECK: PDA del programa que autoriza los movimientos de posiciones en primary_market
+    #[account(seeds = [MARKET_AUTHORITY_SEED], bump)]
+    pub market_authority: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub primary_market_program: Program<'info, PrimaryMarket>,
+    pub system_program: Program<'info, System>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseEscrow<'info> {
+    /// Paga la renta de la posición del vendedor si no existe
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Vendedor original, validado contra el listing
+    #[account(address = listing.seller)]
+    pub seller: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"listing", listing.seller.as_ref(), listing.nft_mint.as_ref()],
+        bump = listing.bump
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// Token account del escrow
+    #[account(
+        mut,
+        constraint = escrow_token_account.mint == listing.nft_mint,
+        constraint = escrow_token_account.owner == listing.key()
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    /// Token account del vendedor (a donde vuelve la unidad)
+    #[account(
+        mut,
+        constraint = seller_token_account.mint == listing.nft_mint,
+        constraint = seller_token_account.owner == listing.seller
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+
+    /// Venta primaria asociada al listing
+    #[account(address = listing.sale)]
+    pub sale: Account<'info, Sale>,
+
+    /// Trailer al que pertenece el mint listado
+    #[account(
+        seeds = [b"trailer", sale.mint.as_ref()],
+        bump = trailer_asset.bump,
+        seeds::program = asset_nft::ID
+    )]
+    pub trailer_asset: Account<'info, TrailerAsset>,
+
+    /// Posición del escrow del listing
+    #[account(
+        mut,
+        seeds = [b"position", sale.key().as_ref(), listing.key().as_ref()],
+        bump = listing_position.bump,
+        seeds::program = primary_market::ID
+    )]
+    pub listing_position: Account<'info, Position>,
+
+    /// CHECK: Posición del vendedor, creada y validada por primary_market
+    #[account(mut)]
+    pub seller_position: UncheckedAccount<'info>,
+
+    /// CHECK: PDA del programa que autoriza los movimientos de posiciones en primary_market
+    #[account(seeds = [MARKET_AUTHORITY_SEED], bump)]
+    pub market_authority: UncheckedAccount<'info>,
+
     pub token_program: Program<'info, Token>,
+    pub primary_market_program: Program<'info, PrimaryMarket>,
+    pub system_program: Program<'info, System>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitFxRate<'info> {
+    #[account(mut, address = guardian_config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    #[account(init, payer = admin, space = 8 + FxRate::INIT_SPACE, seeds = [b"fx_rate"], bump)]
+    pub fx_rate: Account<'info, FxRate>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetFxRate<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(mut, seeds = [b"fx_rate"], bump = fx_rate.bump, has_one = authority)]
+    pub fx_rate: Account<'info, FxRate>,
+
+    #[account(seeds = [b"guardian"], bump = guardian_config.bump, seeds::program = asset_nft::ID)]
+    pub guardian_config: Account<'info, GuardianConfig>,
 }
 
 #[account]
@@ -319,6 +731,8 @@ pub struct Listing {
     pub seller: Pubkey,           // 32 bytes
     /// Mint address del NFT
     pub nft_mint: Pubkey,          // 32 bytes
+    /// Venta primaria donde se registran las posiciones
+    pub sale: Pubkey,              // 32 bytes
     /// Precio de venta en lamports
     pub price: u64,                // 8 bytes
     /// Fecha de compra original (unix timestamp)
@@ -332,7 +746,33 @@ pub struct Listing {
 }
 
 impl Listing {
-    pub const INIT_SPACE: usize = 32 + 32 + 8 + 8 + 8 + 1 + 1; // 90 bytes
+    pub const INIT_SPACE: usize = 32 + 32 + 32 + 8 + 8 + 8 + 1 + 1; // 122 bytes
+}
+
+/// Cotización de la moneda de pago de los trailers (USDC) expresada en lamports
+#[account]
+#[derive(InitSpace)]
+pub struct FxRate {
+    /// Oráculo o multisig que publica la cotización
+    pub authority: Pubkey,
+    /// Lamports por unidad entera de la moneda de pago
+    pub lamports_per_unit: u64,
+    /// Decimales de la moneda de pago (6 para USDC)
+    pub unit_decimals: u8,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl FxRate {
+    pub fn is_stale(&self, now: i64) -> bool {
+        now - self.updated_at > MAX_FX_RATE_AGE
+    }
+
+    /// Convierte un monto en unidades base de la moneda de pago a lamports
+    pub fn to_lamports(&self, amount: u64) -> Result<u64> {
+        let lamports = amount as u128 * self.lamports_per_unit as u128 / 10u128.pow(self.unit_decimals as u32);
+        u64::try_from(lamports).map_err(|_| error!(ErrorCode::ArithmeticError))
+    }
 }
 
 #[error_code]
@@ -349,4 +789,20 @@ pub enum ErrorCode {
     InvalidOwner,
     #[msg("Error aritmético")]
     ArithmeticError,
+    #[msg("El trailer no admite negociación en su estado actual")]
+    NotTradable,
+    #[msg("El mint no corresponde a la venta")]
+    MintMismatch,
+    #[msg("Precio por debajo del NAV por token")]
+    PriceBelowNav,
+    #[msg("El trailer no está liquidado ni en redención")]
+    NotWindingDown,
+    #[msg("El trailer está bloqueado")]
+    AssetLocked,
+    #[msg("La tesorería no corresponde a la serie del trailer")]
+    InvalidTreasury,
+    #[msg("Cotización inválida")]
+    InvalidFxRate,
+    #[msg("Solo el admin del protocolo puede crear la cotización")]
+    Unauthorized,
 }
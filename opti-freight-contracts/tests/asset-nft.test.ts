@@ -0,0 +1,222 @@
+import * as anchor from "@coral-xyz/anchor";
+import { expect } from "chai";
+import { BN } from "bn.js";
+import { SystemProgram } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import {
+  provider,
+  assetNft,
+  primaryMarket,
+  pda,
+  setupTrailerSale,
+  fundedBuyer,
+  purchaseAccounts,
+  SaleFixture,
+} from "./fixtures";
+
+describe("asset-nft", () => {
+  describe("Redemption", () => {
+    const TOTAL_TOKENS = 100;
+    const SOLD = 40;
+    const PROCEEDS = new BN(1_000_000_000); // 1000 USDC
+
+    let fx: SaleFixture;
+    let holder: anchor.web3.Keypair;
+    const approver = anchor.web3.Keypair.generate();
+    let holderUsdc: anchor.web3.PublicKey;
+    let holderUnits: anchor.web3.PublicKey;
+    let redemption: anchor.web3.PublicKey;
+    let redemptionVault: anchor.web3.PublicKey;
+
+    before(async () => {
+      fx = await setupTrailerSale({ totalTokens: TOTAL_TOKENS, tokenPrice: new BN(1_000_000) });
+
+      const funded = await fundedBuyer(fx, 100_000_000);
+      holder = funded.buyer;
+      holderUsdc = funded.usdc;
+      const accounts = purchaseAccounts(fx, holder.publicKey, 0);
+      await primaryMarket.methods
+        .buy(SOLD)
+        .accounts({ ...accounts, buyerTokenAccount: holderUsdc })
+        .signers([holder])
+        .rpc();
+
+      holderUnits = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, holder, fx.mint, holder.publicKey)
+      ).address;
+      await primaryMarket.methods
+        .finalizePurchase()
+        .accounts({ ...accounts, buyerAssetAccount: holderUnits, sellerTokenAccount: fx.sellerUsdc })
+        .rpc();
+
+      await mintTo(provider.connection, fx.authority, fx.usdcMint, fx.sellerUsdc, fx.authority, PROCEEDS.toNumber());
+
+      redemption = pda([Buffer.from("redemption"), fx.trailerAsset.toBuffer()], assetNft.programId);
+      redemptionVault = pda([Buffer.from("redemption_vault"), redemption.toBuffer()], assetNft.programId);
+    });
+
+    it("Rejects the authority approving its own liquidation", async () => {
+      try {
+        await assetNft.methods
+          .setLiquidationApprover(fx.authority.publicKey)
+          .accounts({
+            liquidationApprover: fx.authority.publicKey,
+            trailerAsset: fx.trailerAsset,
+            guardianConfig: fx.guardianConfig,
+          })
+          .signers([fx.authority])
+          .rpc();
+        expect.fail("the authority can't be its own approver");
+      } catch (err) {
+        expect(String(err)).to.include("ApproverIsAuthority");
+      }
+
+      await assetNft.methods
+        .setLiquidationApprover(approver.publicKey)
+        .accounts({
+          liquidationApprover: fx.authority.publicKey,
+          trailerAsset: fx.trailerAsset,
+          guardianConfig: fx.guardianConfig,
+        })
+        .signers([fx.authority])
+        .rpc();
+    });
+
+    it("Counts the units sold as outstanding", async () => {
+      await assetNft.methods
+        .liquidate({ totalLoss: {} }, Array(32).fill(7), PROCEEDS, new BN(Math.floor(Date.now() / 1000) + 3600))
+        .accounts({
+          authority: fx.authority.publicKey,
+          liquidationApprover: approver.publicKey,
+          trailerAsset: fx.trailerAsset,
+          redemption,
+          paymentMint: fx.usdcMint,
+          redemptionVault,
+          authorityPaymentAccount: fx.sellerUsdc,
+          guardianConfig: fx.guardianConfig,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([fx.authority, approver])
+        .rpc();
+
+      const state = await assetNft.account.redemption.fetch(redemption);
+      expect(state.unitsOutstanding.toNumber()).to.equal(SOLD);
+      expect(state.totalProceeds.toString()).to.equal(PROCEEDS.toString());
+
+      const trailer = await assetNft.account.trailerAsset.fetch(fx.trailerAsset);
+      expect(trailer.status).to.deep.equal({ liquidated: {} });
+    });
+
+    it("Pays holders pro rata and closes once every unit is redeemed", async () => {
+      const redeemAccounts = {
+        holder: holder.publicKey,
+        trailerAsset: fx.trailerAsset,
+        mint: fx.mint,
+        redemption,
+        redemptionVault,
+        holderTokenAccount: holderUnits,
+        holderPaymentAccount: holderUsdc,
+        freezeAuthority: pda([Buffer.from("freeze_authority"), fx.mint.toBuffer()], assetNft.programId),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        guardianConfig: fx.guardianConfig,
+      };
+      const before = new BN((await provider.connection.getTokenAccountBalance(holderUsdc)).value.amount);
+
+      try {
+        await assetNft.methods.redeem(new BN(10)).accounts(redeemAccounts).signers([holder]).rpc();
+        expect.fail("redeem should wait for final proceeds");
+      } catch (err) {
+        expect(String(err)).to.include("ProceedsNotFinal");
+      }
+
+      await assetNft.methods
+        .finalizeRedemption()
+        .accounts({
+          authority: fx.authority.publicKey,
+          trailerAsset: fx.trailerAsset,
+          redemption,
+          redemptionVault,
+          guardianConfig: fx.guardianConfig,
+        })
+        .signers([fx.authority])
+        .rpc();
+
+      await assetNft.methods.redeem(new BN(10)).accounts(redeemAccounts).signers([holder]).rpc();
+      let after = new BN((await provider.connection.getTokenAccountBalance(holderUsdc)).value.amount);
+      expect(after.sub(before).toString()).to.equal(PROCEEDS.muln(10).divn(SOLD).toString());
+
+      await assetNft.methods.redeem(new BN(SOLD - 10)).accounts(redeemAccounts).signers([holder]).rpc();
+      after = new BN((await provider.connection.getTokenAccountBalance(holderUsdc)).value.amount);
+      expect(after.sub(before).toString()).to.equal(PROCEEDS.toString());
+
+      const vault = await provider.connection.getTokenAccountBalance(redemptionVault);
+      expect(vault.value.amount).to.equal("0");
+      const units = await provider.connection.getTokenAccountBalance(holderUnits);
+      expect(units.value.amount).to.equal("0");
+
+      const state = await assetNft.account.redemption.fetch(redemption);
+      expect(state.unitsRedeemed.toNumber()).to.equal(SOLD);
+      expect(state.closedAt.toNumber()).to.be.greaterThan(0);
+      const trailer = await assetNft.account.trailerAsset.fetch(fx.trailerAsset);
+      expect(trailer.status).to.deep.equal({ redeemed: {} });
+    });
+  });
+
+  describe("Guardian pause", () => {
+    const WHOLE_PROGRAM = Array(8).fill(0);
+    let fx: SaleFixture;
+
+    before(async () => {
+      fx = await setupTrailerSale({ totalTokens: 10, tokenPrice: new BN(1_000_000) });
+    });
+
+    it("Blocks state changes but keeps views and the guardian's own instructions available", async () => {
+      await assetNft.methods
+        .guardianPause(assetNft.programId, WHOLE_PROGRAM, new BN(600))
+        .accounts({ guardian: provider.wallet.publicKey, guardianConfig: fx.guardianConfig })
+        .rpc();
+
+      try {
+        await assetNft.methods
+          .updateMetadata("https://optifreight.io/trailer-v2.json")
+          .accounts({
+            metadataManager: fx.authority.publicKey,
+            trailerAsset: fx.trailerAsset,
+            guardianConfig: fx.guardianConfig,
+          })
+          .signers([fx.authority])
+          .rpc();
+        expect.fail("update_metadata should fail while asset_nft is paused");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("GuardianPaused");
+      }
+
+      // Read-only views are exempt
+      const nav = await assetNft.methods.getNav().accounts({ trailerAsset: fx.trailerAsset }).view();
+      expect(nav.navPerToken.toNumber()).to.be.greaterThan(0);
+
+      // guardian_unpause is exempt, otherwise a pause could only wait out its expiry
+      await assetNft.methods
+        .guardianUnpause(assetNft.programId, WHOLE_PROGRAM)
+        .accounts({ guardian: provider.wallet.publicKey, guardianConfig: fx.guardianConfig })
+        .rpc();
+
+      await assetNft.methods
+        .updateMetadata("https://optifreight.io/trailer-v2.json")
+        .accounts({
+          metadataManager: fx.authority.publicKey,
+          trailerAsset: fx.trailerAsset,
+          guardianConfig: fx.guardianConfig,
+        })
+        .signers([fx.authority])
+        .rpc();
+      const trailer = await assetNft.account.trailerAsset.fetch(fx.trailerAsset);
+      expect(trailer.uri).to.equal("https://optifreight.io/trailer-v2.json");
+    });
+  });
+});
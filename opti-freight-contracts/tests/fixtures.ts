@@ -0,0 +1,241 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  LAMPORTS_PER_SOL,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { BN } from "bn.js";
+
+export const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+export const assetNft = anchor.workspace.AssetNft as Program<any>;
+export const primaryMarket = anchor.workspace.PrimaryMarket as Program<any>;
+
+export const pda = (seeds: (Buffer | Uint8Array)[], programId: PublicKey) =>
+  PublicKey.findProgramAddressSync(seeds, programId)[0];
+
+export const guardianPda = () => pda([Buffer.from("guardian")], assetNft.programId);
+
+export async function fundedKeypair(sol = 5): Promise<Keypair> {
+  const keypair = Keypair.generate();
+  const sig = await provider.connection.requestAirdrop(
+    keypair.publicKey,
+    sol * LAMPORTS_PER_SOL
+  );
+  await provider.connection.confirmTransaction(sig, "confirmed");
+  return keypair;
+}
+
+/** ProgramData account of an upgradeable program */
+export const programDataPda = (programId: PublicKey) =>
+  pda([programId.toBuffer()], new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111"));
+
+/**
+ * The guardian config is a protocol-wide singleton, create it only once. Only
+ * the upgrade authority (the local deploy wallet) may initialize it.
+ */
+export async function ensureGuardian(): Promise<PublicKey> {
+  const guardianConfig = guardianPda();
+  const existing = await provider.connection.getAccountInfo(guardianConfig);
+  if (!existing) {
+    await assetNft.methods
+      .initGuardian(provider.wallet.publicKey)
+      .accounts({
+        admin: provider.wallet.publicKey,
+        program: assetNft.programId,
+        programData: programDataPda(assetNft.programId),
+        guardianConfig,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+  return guardianConfig;
+}
+
+export interface SaleOptions {
+  totalTokens: number;
+  tokenPrice: BN;
+  maxPerWallet?: number;
+  maxWalletBps?: number;
+  coolingOffPeriod?: number;
+}
+
+export interface SaleFixture {
+  authority: Keypair;
+  usdcMint: PublicKey;
+  sellerUsdc: PublicKey;
+  mint: PublicKey;
+  series: PublicKey;
+  trailerAsset: PublicKey;
+  sale: PublicKey;
+  paymentVault: PublicKey;
+  tokenVault: PublicKey;
+  guardianConfig: PublicKey;
+}
+
+/**
+ * Series + trailer + primary sale with the full inventory in the vault and the
+ * sale PDA registered as the trailer's market authority
+ */
+export async function setupTrailerSale(opts: SaleOptions): Promise<SaleFixture> {
+  const authority = await fundedKeypair(10);
+  const guardianConfig = await ensureGuardian();
+
+  const usdcMint = await createMint(provider.connection, authority, authority.publicKey, null, 6);
+  const sellerUsdc = (
+    await getOrCreateAssociatedTokenAccount(provider.connection, authority, usdcMint, authority.publicKey)
+  ).address;
+
+  const seriesName = `S-${authority.publicKey.toBase58().slice(0, 8)}`;
+  const series = pda([Buffer.from("series"), Buffer.from(seriesName)], assetNft.programId);
+  await assetNft.methods
+    .initSeries(
+      seriesName,
+      "https://optifreight.io/series.json",
+      {
+        minApy: 0,
+        maxApy: 5000,
+        minTermYears: 1,
+        maxTermYears: 10,
+        minTotalTokens: 1,
+        maxTotalTokens: 10_000,
+      },
+      0,
+      new BN(0)
+    )
+    .accounts({ authority: authority.publicKey, series, systemProgram: SystemProgram.programId })
+    .signers([authority])
+    .rpc();
+
+  // Fraction mint: 0 decimals, freeze authority held by the asset_nft PDA
+  const mintKeypair = Keypair.generate();
+  const freezeAuthority = pda(
+    [Buffer.from("freeze_authority"), mintKeypair.publicKey.toBuffer()],
+    assetNft.programId
+  );
+  const mint = await createMint(
+    provider.connection,
+    authority,
+    authority.publicKey,
+    freezeAuthority,
+    0,
+    mintKeypair
+  );
+
+  const trailerAsset = pda([Buffer.from("trailer"), mint.toBuffer()], assetNft.programId);
+  await assetNft.methods
+    .createTrailerNft(
+      "Trailer Test",
+      "TRL",
+      "https://optifreight.io/trailer.json",
+      seriesName,
+      opts.tokenPrice.mul(new BN(opts.totalTokens)),
+      opts.tokenPrice,
+      opts.totalTokens,
+      1200,
+      5
+    )
+    .accounts({
+      authority: authority.publicKey,
+      seriesAccount: series,
+      trailerAsset,
+      mint,
+      guardianConfig,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    })
+    .signers([authority])
+    .rpc();
+
+  const sale = pda([Buffer.from("sale"), mint.toBuffer()], primaryMarket.programId);
+  const paymentVault = pda([Buffer.from("payment_vault"), sale.toBuffer()], primaryMarket.programId);
+  const tokenVault = pda([Buffer.from("token_vault"), sale.toBuffer()], primaryMarket.programId);
+  await primaryMarket.methods
+    .initSale(
+      opts.tokenPrice,
+      opts.totalTokens,
+      opts.maxPerWallet ?? 0,
+      opts.maxWalletBps ?? 0,
+      new BN(opts.coolingOffPeriod ?? 0)
+    )
+    .accounts({
+      authority: authority.publicKey,
+      sale,
+      mint,
+      trailerAsset,
+      paymentMint: usdcMint,
+      sellerTokenAccount: sellerUsdc,
+      paymentVault,
+      tokenVault,
+      guardianConfig,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([authority])
+    .rpc();
+
+  await assetNft.methods
+    .setMarketAuthority(sale)
+    .accounts({ authority: authority.publicKey, trailerAsset, guardianConfig })
+    .signers([authority])
+    .rpc();
+
+  await mintTo(provider.connection, authority, mint, tokenVault, authority, opts.totalTokens);
+
+  return {
+    authority,
+    usdcMint,
+    sellerUsdc,
+    mint,
+    series,
+    trailerAsset,
+    sale,
+    paymentVault,
+    tokenVault,
+    guardianConfig,
+  };
+}
+
+/** Wallet with SOL and `usdc` base units of the sale's payment mint */
+export async function fundedBuyer(fx: SaleFixture, usdc: number): Promise<{ buyer: Keypair; usdc: PublicKey }> {
+  const buyer = await fundedKeypair();
+  const account = (
+    await getOrCreateAssociatedTokenAccount(provider.connection, buyer, fx.usdcMint, buyer.publicKey)
+  ).address;
+  await mintTo(provider.connection, fx.authority, fx.usdcMint, account, fx.authority, usdc);
+  return { buyer, usdc: account };
+}
+
+/** Accounts shared by `buy`, `cancel_purchase` and `finalize_purchase` */
+export function purchaseAccounts(fx: SaleFixture, buyer: PublicKey, purchaseId: number) {
+  return {
+    sale: fx.sale,
+    buyer,
+    position: pda(
+      [Buffer.from("position"), fx.sale.toBuffer(), buyer.toBuffer()],
+      primaryMarket.programId
+    ),
+    purchase: pda(
+      [Buffer.from("purchase"), fx.sale.toBuffer(), new BN(purchaseId).toArrayLike(Buffer, "le", 8)],
+      primaryMarket.programId
+    ),
+    paymentVault: fx.paymentVault,
+    tokenVault: fx.tokenVault,
+    trailerAsset: fx.trailerAsset,
+    series: fx.series,
+    assetNftProgram: assetNft.programId,
+    guardianConfig: fx.guardianConfig,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    systemProgram: SystemProgram.programId,
+  };
+}
@@ -0,0 +1,175 @@
+import { expect } from "chai";
+import { BN } from "bn.js";
+import { Keypair } from "@solana/web3.js";
+import { getOrCreateAssociatedTokenAccount } from "@solana/spl-token";
+import {
+  provider,
+  primaryMarket,
+  setupTrailerSale,
+  fundedBuyer,
+  purchaseAccounts,
+  SaleFixture,
+} from "./fixtures";
+
+describe("primary-market", () => {
+  const TOKEN_PRICE = new BN(200_000_000); // $200 USDC (6 decimals)
+
+  describe("Per-wallet cap", () => {
+    let fx: SaleFixture;
+    let holder: Keypair;
+
+    before(async () => {
+      // 1000 tokens, 5% por wallet = 50 tokens, tope absoluto 80 (gana el menor)
+      fx = await setupTrailerSale({
+        totalTokens: 1000,
+        tokenPrice: TOKEN_PRICE,
+        maxPerWallet: 80,
+        maxWalletBps: 500,
+      });
+    });
+
+    it("Accumulates purchases up to the cap and rejects the excess", async () => {
+      const { buyer, usdc } = await fundedBuyer(fx, 20_000_000_000);
+      holder = buyer;
+
+      await primaryMarket.methods
+        .buy(30)
+        .accounts({ ...purchaseAccounts(fx, buyer.publicKey, 0), buyerTokenAccount: usdc })
+        .signers([buyer])
+        .rpc();
+      await primaryMarket.methods
+        .buy(20)
+        .accounts({ ...purchaseAccounts(fx, buyer.publicKey, 1), buyerTokenAccount: usdc })
+        .signers([buyer])
+        .rpc();
+
+      const position = await primaryMarket.account.position.fetch(
+        purchaseAccounts(fx, buyer.publicKey, 0).position
+      );
+      expect(position.amount).to.equal(50);
+
+      try {
+        await primaryMarket.methods
+          .buy(1)
+          .accounts({ ...purchaseAccounts(fx, buyer.publicKey, 2), buyerTokenAccount: usdc })
+          .signers([buyer])
+          .rpc();
+        expect.fail("buy above the wallet cap should fail");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("WalletLimitExceeded");
+      }
+    });
+
+    it("Rejects a direct transfer_position not signed by the secondary market", async () => {
+      // El tenedor firma como dueño pero no puede firmar por el PDA del mercado secundario
+      const buyer = holder;
+      const accounts = purchaseAccounts(fx, buyer.publicKey, 0);
+
+      try {
+        await primaryMarket.methods
+          .transferPosition(1)
+          .accounts({
+            marketAuthority: buyer.publicKey,
+            owner: buyer.publicKey,
+            payer: buyer.publicKey,
+            sale: fx.sale,
+            fromPosition: accounts.position,
+            recipient: fx.authority.publicKey,
+            toPosition: purchaseAccounts(fx, fx.authority.publicKey, 0).position,
+            systemProgram: accounts.systemProgram,
+            guardianConfig: fx.guardianConfig,
+          })
+          .signers([buyer])
+          .rpc();
+        expect.fail("transfer_position outside the secondary market should fail");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("ConstraintSeeds");
+      }
+    });
+
+    it("Rejects a bps cap that rounds to zero tokens", async () => {
+      try {
+        // 1 bps de 50 tokens = 0
+        await setupTrailerSale({ totalTokens: 50, tokenPrice: TOKEN_PRICE, maxWalletBps: 1 });
+        expect.fail("init_sale with a zero cap should fail");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("InvalidLimit");
+      }
+    });
+  });
+
+  describe("Cooling-off period", () => {
+    let fx: SaleFixture;
+
+    before(async () => {
+      fx = await setupTrailerSale({
+        totalTokens: 100,
+        tokenPrice: TOKEN_PRICE,
+        coolingOffPeriod: 3600,
+      });
+    });
+
+    it("Refunds the full cost and releases the reserved tokens on cancel", async () => {
+      const { buyer, usdc } = await fundedBuyer(fx, 2_000_000_000);
+      const accounts = purchaseAccounts(fx, buyer.publicKey, 0);
+
+      await primaryMarket.methods
+        .buy(5)
+        .accounts({ ...accounts, buyerTokenAccount: usdc })
+        .signers([buyer])
+        .rpc();
+
+      let sale = await primaryMarket.account.sale.fetch(fx.sale);
+      expect(sale.sold).to.equal(5);
+      expect(sale.pendingTokens).to.equal(5);
+      const escrowed = await provider.connection.getTokenAccountBalance(fx.paymentVault);
+      expect(escrowed.value.amount).to.equal(TOKEN_PRICE.muln(5).toString());
+
+      await primaryMarket.methods
+        .cancelPurchase()
+        .accounts({ ...accounts, buyerTokenAccount: usdc })
+        .signers([buyer])
+        .rpc();
+
+      sale = await primaryMarket.account.sale.fetch(fx.sale);
+      expect(sale.sold).to.equal(0);
+      expect(sale.pendingTokens).to.equal(0);
+      expect(sale.pendingPurchases).to.equal(0);
+
+      const refunded = await provider.connection.getTokenAccountBalance(usdc);
+      expect(refunded.value.amount).to.equal("2000000000");
+      const position = await primaryMarket.account.position.fetch(accounts.position);
+      expect(position.amount).to.equal(0);
+      expect(await provider.connection.getAccountInfo(accounts.purchase)).to.be.null;
+    });
+
+    it("Does not finalize while the cooling-off window is open", async () => {
+      const { buyer, usdc } = await fundedBuyer(fx, 2_000_000_000);
+      const sale = await primaryMarket.account.sale.fetch(fx.sale);
+      const accounts = purchaseAccounts(fx, buyer.publicKey, sale.nextPurchaseId.toNumber());
+
+      await primaryMarket.methods
+        .buy(2)
+        .accounts({ ...accounts, buyerTokenAccount: usdc })
+        .signers([buyer])
+        .rpc();
+
+      const buyerAssetAccount = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, buyer, fx.mint, buyer.publicKey)
+      ).address;
+      try {
+        await primaryMarket.methods
+          .finalizePurchase()
+          .accounts({
+            ...accounts,
+            buyerAssetAccount,
+            sellerTokenAccount: fx.sellerUsdc,
+          })
+          .rpc();
+        expect.fail("finalize inside the cooling-off window should fail");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("CoolingOffActive");
+      }
+    });
+  });
+});
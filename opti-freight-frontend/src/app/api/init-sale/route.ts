@@ -3,6 +3,7 @@ import { Connection, Keypair, PublicKey, SystemProgram, Transaction } from '@sol
 import { Program, AnchorProvider } from '@coral-xyz/anchor';
 import bs58 from 'bs58';
 import optiFreightIdl from '@/lib/idl/opti_freight.json';
+import { GUARDIAN_CONFIG } from '@/lib/anchor/program-ids';
 
 const PROGRAM_ID = new PublicKey('7x4rNdNN9Szce8qfasDGiV3srApWcJ339t8iGAyKjrga');
 
@@ -116,6 +117,7 @@ export async function POST() {
     console.log('  authority:', treasuryKeypair.publicKey.toString());
     console.log('  sale:', salePda.toString());
     console.log('  systemProgram:', SystemProgram.programId.toString());
+    console.log('  guardianConfig:', GUARDIAN_CONFIG.toString());
 
     const tx = await program.methods
       .initSale()
@@ -123,6 +125,7 @@ export async function POST() {
         authority: treasuryKeypair.publicKey,
         sale: salePda,
         systemProgram: SystemProgram.programId,
+        guardianConfig: GUARDIAN_CONFIG,
       })
       .rpc();
 
@@ -12,6 +12,7 @@ import { PublicKey, SystemProgram, LAMPORTS_PER_SOL, Connection } from '@solana/
 import { useConnection, useWallet } from '@solana/wallet-adapter-react';
 import { Program, AnchorProvider, web3, BN } from '@coral-xyz/anchor';
 import optiFreightIdl from '@/lib/idl/opti_freight.json';
+import { GUARDIAN_CONFIG } from '@/lib/anchor/program-ids';
 
 // Constantes del Programa (SOL)
 const PROGRAM_ID = new PublicKey('7x4rNdNN9Szce8qfasDGiV3srApWcJ339t8iGAyKjrga');
@@ -36,6 +37,7 @@ interface Sale {
   total: number;
   sold: number;
   active: boolean;
+  paused: boolean;
   bump: number;
 }
 
@@ -116,6 +118,7 @@ export function useOptiFreight() {
           authority: wallet.publicKey,
           sale: salePda,
           systemProgram: SystemProgram.programId,
+          guardianConfig: GUARDIAN_CONFIG,
         })
         .rpc();
 
@@ -186,6 +189,7 @@ export function useOptiFreight() {
               seller: TREASURY_AUTHORITY,
               platform: PLATFORM_WALLET,
               systemProgram: SystemProgram.programId,
+              guardianConfig: GUARDIAN_CONFIG,
             })
             .rpc();
 
@@ -250,6 +254,7 @@ export function useOptiFreight() {
             seller: wallet.publicKey,
             listing: listingPda,
             systemProgram: SystemProgram.programId,
+            guardianConfig: GUARDIAN_CONFIG,
           })
           .rpc();
 
@@ -310,6 +315,7 @@ export function useOptiFreight() {
             seller: listing.seller,
             platform: PLATFORM_WALLET,
             systemProgram: SystemProgram.programId,
+            guardianConfig: GUARDIAN_CONFIG,
           })
           .rpc();
 
@@ -365,6 +371,7 @@ export function useOptiFreight() {
             user: wallet.publicKey,
             pool: poolPda,
             systemProgram: SystemProgram.programId,
+            guardianConfig: GUARDIAN_CONFIG,
           })
           .rpc();
 
@@ -409,6 +416,7 @@ export function useOptiFreight() {
             authority: wallet.publicKey,
             sale: salePublicKey,
             systemProgram: SystemProgram.programId,
+            guardianConfig: GUARDIAN_CONFIG,
           })
           .rpc();
 
@@ -445,6 +453,7 @@ export function useOptiFreight() {
             seller: wallet.publicKey,
             listing: listingPublicKey,
             systemProgram: SystemProgram.programId,
+            guardianConfig: GUARDIAN_CONFIG,
           })
           .rpc();
 
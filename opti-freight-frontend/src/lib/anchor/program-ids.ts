@@ -18,3 +18,12 @@ export const PROGRAM_IDS = {
 } as const;
 
 export type ProgramId = keyof typeof PROGRAM_IDS;
+
+/**
+ * Protocol-wide guardian pause config, owned by asset_nft. Every
+ * state-changing instruction takes it as an account.
+ */
+export const GUARDIAN_CONFIG = PublicKey.findProgramAddressSync(
+  [Buffer.from('guardian')],
+  PROGRAM_IDS.ASSET_NFT
+)[0];